@@ -2,3 +2,30 @@ pub const MAX_NAME_LENGTH: usize = 32;
 pub const MAX_METRICS: u16 = 100;
 pub const MAX_ALERTS: u16 = 50;
 pub const MAX_DATA_POINTS: u32 = 1000;
+pub const MAX_HISTORY_POINTS: u32 = 4096;
+pub const MAX_PAGE_POINTS: usize = 256;
+/// Slots of each history page kept free for late points once the page
+/// stops taking new ones
+pub const PAGE_LATE_SLOTS: usize = 32;
+pub const MAX_RETAINED_PAGES: u32 = 16;
+/// Retained history pages plus the one being written
+pub const MAX_OPEN_PAGES: usize = MAX_RETAINED_PAGES as usize + 1;
+pub const MAX_ROLLUP_TIERS: usize = 3;
+pub const MAX_ROLLUP_BUCKETS: usize = 128;
+pub const MAX_HISTOGRAM_BUCKETS: usize = 16;
+/// Bucket counts, including the trailing overflow bucket
+pub const MAX_HISTOGRAM_COUNTS: usize = MAX_HISTOGRAM_BUCKETS + 1;
+pub const MAX_MAINTENANCE_WINDOWS: usize = 8;
+pub const MAX_COMPOSITE_CONDITIONS: usize = 8;
+pub const MAX_WEBHOOK_URL_LENGTH: usize = 200;
+pub const MAX_UNIT_LENGTH: usize = 16;
+pub const MAX_SCALE: u8 = 18;
+pub const BASIS_POINTS: u16 = 10_000;
+pub const DEFAULT_EWMA_ALPHA_BPS: u16 = 2_000;
+/// Executable bytes hashed by a program watch, keeping the check within the
+/// default compute budget
+pub const MAX_CODE_HASH_LEN: usize = 64 * 1024;
+/// Shortest interval between samples of a sourced metric, in seconds
+pub const MIN_SAMPLE_INTERVAL: u32 = 10;
+/// Decimal places of the fixed-point counter rate
+pub const COUNTER_RATE_DECIMALS: u32 = 6;
@@ -11,4 +11,128 @@ pub enum MonitoringError {
     InvalidMetricParams,
     #[msg("Invalid alert configuration")]
     InvalidAlertConfig,
-}
\ No newline at end of file
+    #[msg("Timestamp is ahead of the cluster clock")]
+    TimestampInFuture,
+    #[msg("Metric already has a history account attached")]
+    HistoryAlreadyAttached,
+    #[msg("History account does not match the metric")]
+    HistoryAccountMismatch,
+    #[msg("Alert is not configured for this metric")]
+    AlertMetricMismatch,
+    #[msg("Alert account must be writable")]
+    AlertNotWritable,
+    #[msg("Monitoring state has reached its metric limit")]
+    MetricLimitReached,
+    #[msg("Monitoring state has reached its alert limit")]
+    AlertLimitReached,
+    #[msg("Metric updated more frequently than the configured minimum interval")]
+    UpdateTooFrequent,
+    #[msg("Metric account must be writable")]
+    MetricNotWritable,
+    #[msg("Metric account appears more than once in the batch")]
+    DuplicateBatchMetric,
+    #[msg("Metric does not belong to this monitoring state")]
+    MetricMonitoringMismatch,
+    #[msg("Signer is not the metric authority")]
+    Unauthorized,
+    #[msg("Batch entry references a missing metric account")]
+    InvalidBatchEntry,
+    #[msg("All alerts bound to the metric must be supplied")]
+    MissingMetricAlerts,
+    #[msg("Alert is not firing")]
+    AlertNotFiring,
+    #[msg("Maintenance window must end after it starts and in the future")]
+    InvalidMaintenanceWindow,
+    #[msg("Monitoring state has reached its maintenance window limit")]
+    MaintenanceWindowLimitReached,
+    #[msg("Maintenance window not found")]
+    MaintenanceWindowNotFound,
+    #[msg("Invalid composite alert definition")]
+    InvalidCompositeAlert,
+    #[msg("An account referenced by the composite alert was not supplied")]
+    CompositeMemberMissing,
+    #[msg("Account is not a chain-watch alert, metric, composite alert, rollup or history page")]
+    UnexpectedAccount,
+    #[msg("Alert is not a staleness alert")]
+    NotStalenessAlert,
+    #[msg("Sourced metrics can only be updated through sample_metric")]
+    SourcedMetric,
+    #[msg("Metric has no on-chain source")]
+    MetricNotSourced,
+    #[msg("Source account does not match the metric's source")]
+    SourceAccountMismatch,
+    #[msg("Token amount does not fit in a signed 64-bit integer")]
+    TokenAmountOverflow,
+    #[msg("ProgramData account does not belong to the watched program")]
+    ProgramDataMismatch,
+    #[msg("Watched byte range is empty or outside the account data")]
+    InvalidWatchRange,
+    #[msg("Account does not match the watch's target")]
+    WatchTargetMismatch,
+    #[msg("Metric page must be supplied exactly when the metric is paged")]
+    MetricPageMismatch,
+    #[msg("Metric page is still within the retention limit")]
+    MetricPageRetained,
+    #[msg("Rollup accounts do not match the metric's rollup tiers")]
+    RollupTierMismatch,
+    #[msg("Rollup account must be writable")]
+    RollupNotWritable,
+    #[msg("Metric has no TTL configured")]
+    MetricHasNoTtl,
+    #[msg("Invalid maximum metrics configuration")]
+    InvalidMaxMetrics,
+    #[msg("Invalid maximum alerts configuration")]
+    InvalidMaxAlerts,
+    #[msg("Invalid maximum data points configuration")]
+    InvalidMaxDataPoints,
+    #[msg("Metric is currently disabled")]
+    MetricDisabled,
+    #[msg("Invalid timestamp")]
+    InvalidTimestamp,
+    #[msg("Value below configured minimum")]
+    ValueBelowMinimum,
+    #[msg("Value above configured maximum")]
+    ValueAboveMaximum,
+    #[msg("Rate of change exceeded configured maximum")]
+    RateOfChangeExceeded,
+    #[msg("Invalid minimum update interval configuration")]
+    InvalidUpdateInterval,
+    #[msg("Counter values must not be negative")]
+    NegativeCounterValue,
+    #[msg("Histogram bucket bounds must be non-empty and strictly ascending")]
+    InvalidHistogramBuckets,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("EWMA alpha must not exceed 10000 basis points")]
+    InvalidEwmaAlpha,
+    #[msg("Integer sources must be 1, 2, 4 or 8 bytes wide")]
+    InvalidMetricSource,
+    #[msg("Source field lies outside the account data")]
+    SourceOutOfBounds,
+    #[msg("Source value does not fit in a signed 64-bit integer")]
+    SourceValueOverflow,
+    #[msg("Token sources must be sampled with sample_token_metric")]
+    TokenSourceNotSupported,
+    #[msg("Scale must not exceed 18 decimal places")]
+    InvalidScale,
+    #[msg("Unit label too long")]
+    UnitTooLong,
+    #[msg("Rollup tiers must have distinct intervals and a retention of 1 to 128 buckets")]
+    InvalidRollupTiers,
+    #[msg("Data point is older than the late-arrival window allows")]
+    LateArrivalOutsideWindow,
+    #[msg("Metric page is full")]
+    MetricPageFull,
+    #[msg("Kind, source, paging and rollup tiers cannot change after creation")]
+    ImmutableMetricParam,
+    #[msg("All composite alerts depending on the metric must be supplied")]
+    MissingMetricComposites,
+    #[msg("Account supplied more than once")]
+    DuplicateAccount,
+    #[msg("The oldest metric page must be closed before another one is started")]
+    MetricPageNotClosed,
+    #[msg("A metric may retain at most 16 history pages")]
+    InvalidMaxPages,
+    #[msg("Late data point is not newer than the oldest point no longer retained")]
+    LatePointNotRetained,
+}
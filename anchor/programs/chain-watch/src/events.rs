@@ -0,0 +1,327 @@
+// src/events.rs
+use anchor_lang::prelude::*;
+use crate::state::{AlertThresholdType, CompositeCondition, CompositeOperator, RollupInterval};
+
+#[event]
+pub struct MonitoringInitialized {
+    pub monitoring_state: Pubkey,
+    pub authority: Pubkey,
+    pub namespace: u64,
+    pub max_metrics: u16,
+    pub max_alerts: u16,
+    pub max_data_points: u32,
+}
+
+#[event]
+pub struct MonitoringConfigUpdated {
+    pub monitoring_state: Pubkey,
+    pub max_metrics: u16,
+    pub max_alerts: u16,
+    pub max_data_points: u32,
+    pub min_update_interval: i64,
+    pub rate_limiting_enabled: bool,
+}
+
+#[event]
+pub struct MaintenanceWindowAdded {
+    pub monitoring_state: Pubkey,
+    pub window_id: u32,
+    pub start: i64,
+    pub end: i64,
+    pub metric: Option<Pubkey>,
+}
+
+#[event]
+pub struct MaintenanceWindowRemoved {
+    pub monitoring_state: Pubkey,
+    pub window_id: u32,
+}
+
+#[event]
+pub struct MetricAdded {
+    pub monitoring_state: Pubkey,
+    pub metric: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub scale: u8,
+    pub unit: String,
+}
+
+#[event]
+pub struct MetricUpdated {
+    pub metric: Pubkey,
+}
+
+#[event]
+pub struct MetricStatusChanged {
+    pub metric: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct MetricRemoved {
+    pub monitoring_state: Pubkey,
+    pub metric: Pubkey,
+    pub removed_alerts: Vec<Pubkey>,
+}
+
+#[event]
+pub struct MetricHistoryAttached {
+    pub metric: Pubkey,
+    pub history: Pubkey,
+}
+
+#[event]
+pub struct MetricPageClosed {
+    pub metric: Pubkey,
+    pub metric_page: Pubkey,
+    pub page_index: u64,
+}
+
+#[event]
+pub struct MetricRollupInitialized {
+    pub metric: Pubkey,
+    pub metric_rollup: Pubkey,
+    pub interval: RollupInterval,
+}
+
+#[event]
+pub struct MetricRollupClosed {
+    pub metric: Pubkey,
+    pub metric_rollup: Pubkey,
+}
+
+#[event]
+pub struct MetricPruned {
+    pub metric: Pubkey,
+    pub removed: u32,
+    pub refunded: u64,
+}
+
+#[event]
+pub struct MetricRecorded {
+    pub metric: Pubkey,
+    pub timestamp: i64,
+    pub value: i64,
+    pub scale: u8,
+    pub unit: String,
+}
+
+#[event]
+pub struct MetricSampled {
+    pub metric: Pubkey,
+    pub source: Pubkey,
+    pub timestamp: i64,
+    pub value: i64,
+    pub scale: u8,
+    pub unit: String,
+}
+
+#[event]
+pub struct TokenMetricSampled {
+    pub metric: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Option<Pubkey>,
+    pub timestamp: i64,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct MetricPointDeduplicated {
+    pub metric: Pubkey,
+    pub timestamp: i64,
+    pub value: i64,
+}
+
+#[event]
+pub struct MetricRecordRejected {
+    pub metric: Pubkey,
+    pub timestamp: i64,
+    pub value: i64,
+    pub error_code: u64,
+}
+
+#[event]
+pub struct AlertConfigured {
+    pub alert: Pubkey,
+    pub metric: Pubkey,
+    pub alert_id: u16,
+    pub threshold_type: AlertThresholdType,
+    pub threshold_value: i64,
+    pub secondary_threshold: Option<i64>,
+}
+
+#[event]
+pub struct AlertUpdated {
+    pub alert: Pubkey,
+    pub metric: Pubkey,
+    pub threshold_type: AlertThresholdType,
+    pub threshold_value: i64,
+    pub secondary_threshold: Option<i64>,
+}
+
+#[event]
+pub struct AlertStatusChanged {
+    pub alert: Pubkey,
+    pub enabled: bool,
+}
+
+#[event]
+pub struct AlertAcknowledged {
+    pub alert: Pubkey,
+    pub acknowledged_by: Pubkey,
+    pub acknowledged_at: i64,
+}
+
+#[event]
+pub struct AlertSnoozed {
+    pub alert: Pubkey,
+    pub snoozed_until: i64,
+}
+
+#[event]
+pub struct AlertRemoved {
+    pub monitoring_state: Pubkey,
+    pub alert: Pubkey,
+    pub metric: Pubkey,
+}
+
+#[event]
+pub struct CompositeAlertCreated {
+    pub monitoring_state: Pubkey,
+    pub composite_alert: Pubkey,
+    pub operator: CompositeOperator,
+    pub conditions: Vec<CompositeCondition>,
+}
+
+#[event]
+pub struct CompositeAlertTriggered {
+    pub composite_alert: Pubkey,
+    pub triggered_at: i64,
+}
+
+#[event]
+pub struct CompositeAlertResolved {
+    pub composite_alert: Pubkey,
+    pub firing_since: i64,
+    pub resolved_at: i64,
+}
+
+#[event]
+pub struct CompositeAlertAcknowledged {
+    pub composite_alert: Pubkey,
+    pub acknowledged_by: Pubkey,
+    pub acknowledged_at: i64,
+}
+
+#[event]
+pub struct CompositeAlertSnoozed {
+    pub composite_alert: Pubkey,
+    pub snoozed_until: i64,
+}
+
+#[event]
+pub struct CompositeAlertRemoved {
+    pub monitoring_state: Pubkey,
+    pub composite_alert: Pubkey,
+}
+
+#[event]
+pub struct ProgramWatchAdded {
+    pub monitoring_state: Pubkey,
+    pub program_watch: Pubkey,
+    pub program: Pubkey,
+    pub deployed_slot: u64,
+    pub upgrade_authority: Option<Pubkey>,
+    pub code_hash: [u8; 32],
+}
+
+#[event]
+pub struct ProgramUpgraded {
+    pub program_watch: Pubkey,
+    pub program: Pubkey,
+    pub previous_slot: u64,
+    pub deployed_slot: u64,
+    pub previous_hash: [u8; 32],
+    pub code_hash: [u8; 32],
+    pub detected_at: i64,
+}
+
+#[event]
+pub struct ProgramUpgradeAuthorityChanged {
+    pub program_watch: Pubkey,
+    pub program: Pubkey,
+    pub previous_authority: Option<Pubkey>,
+    pub upgrade_authority: Option<Pubkey>,
+    pub detected_at: i64,
+}
+
+#[event]
+pub struct ProgramWatchRemoved {
+    pub monitoring_state: Pubkey,
+    pub program_watch: Pubkey,
+}
+
+#[event]
+pub struct AccountWatchAdded {
+    pub monitoring_state: Pubkey,
+    pub account_watch: Pubkey,
+    pub alert: Pubkey,
+    pub target: Pubkey,
+    pub data_hash: [u8; 32],
+    pub owner: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct AccountChanged {
+    pub account_watch: Pubkey,
+    pub target: Pubkey,
+    pub data_changed: bool,
+    pub previous_hash: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub previous_owner: Pubkey,
+    pub owner: Pubkey,
+    pub previous_lamports: u64,
+    pub lamports: u64,
+    pub detected_at: i64,
+}
+
+#[event]
+pub struct AccountWatchBaselineReset {
+    pub account_watch: Pubkey,
+    pub target: Pubkey,
+    pub data_hash: [u8; 32],
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub reset_at: i64,
+}
+
+#[event]
+pub struct AccountWatchRemoved {
+    pub monitoring_state: Pubkey,
+    pub account_watch: Pubkey,
+}
+
+#[event]
+pub struct AlertTriggered {
+    pub alert: Pubkey,
+    pub metric: Pubkey,
+    pub value: i64,
+    pub scale: u8,
+    pub unit: String,
+    pub consecutive_violations: u8,
+    pub triggered_at: i64,
+}
+
+#[event]
+pub struct AlertResolved {
+    pub alert: Pubkey,
+    pub metric: Pubkey,
+    pub value: i64,
+    pub scale: u8,
+    pub unit: String,
+    pub firing_since: i64,
+    pub resolved_at: i64,
+}
@@ -0,0 +1,157 @@
+// src/instructions/account_watch.rs
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use crate::state::{
+    AccountWatch, AlertConfig, AlertConfigParams, AlertThresholdType, DataRange, MonitoringState,
+};
+use crate::errors::MonitoringError;
+use super::validate_alert_params;
+
+#[derive(Accounts)]
+pub struct WatchAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority,
+        realloc = monitoring_state.space() + 32,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AccountWatch::INIT_SPACE,
+        seeds = [b"account_watch", monitoring_state.key().as_ref(), target.key().as_ref()],
+        bump
+    )]
+    pub account_watch: Account<'info, AccountWatch>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AlertConfig::INIT_SPACE,
+        seeds = [
+            b"alert",
+            monitoring_state.key().as_ref(),
+            account_watch.key().as_ref(),
+            &0u16.to_le_bytes()
+        ],
+        bump
+    )]
+    pub alert_config: Account<'info, AlertConfig>,
+
+    /// CHECK: any account can be watched; only its data, owner and lamports
+    /// are read
+    pub target: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WatchAccount<'info> {
+    pub fn validate(&self, range: Option<DataRange>, params: &AlertConfigParams) -> Result<()> {
+        require!(
+            self.monitoring_state.alert_count() < usize::from(self.monitoring_state.config.max_alerts),
+            MonitoringError::AlertLimitReached
+        );
+        require!(
+            params.threshold_type == AlertThresholdType::AccountChange,
+            MonitoringError::InvalidAlertConfig
+        );
+        if let Some(range) = range {
+            require!(
+                range.fits(self.target.data_len()),
+                MonitoringError::InvalidWatchRange
+            );
+        }
+        validate_alert_params(params)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CheckAccountWatch<'info> {
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        has_one = target @ MonitoringError::WatchTargetMismatch,
+        constraint = account_watch.alert == alert_config.key() @ MonitoringError::AlertMetricMismatch
+    )]
+    pub account_watch: Account<'info, AccountWatch>,
+
+    #[account(mut)]
+    pub alert_config: Account<'info, AlertConfig>,
+
+    /// CHECK: matched against the watch's target
+    pub target: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResetAccountWatch<'info> {
+    #[account(
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        has_one = target @ MonitoringError::WatchTargetMismatch,
+        constraint = account_watch.alert == alert_config.key() @ MonitoringError::AlertMetricMismatch
+    )]
+    pub account_watch: Account<'info, AccountWatch>,
+
+    #[account(mut)]
+    pub alert_config: Account<'info, AlertConfig>,
+
+    /// CHECK: matched against the watch's target
+    pub target: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAccountWatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        constraint = account_watch.alert == alert_config.key() @ MonitoringError::AlertMetricMismatch,
+        close = authority
+    )]
+    pub account_watch: Account<'info, AccountWatch>,
+
+    #[account(
+        mut,
+        close = authority
+    )]
+    pub alert_config: Account<'info, AlertConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// SHA-256 hash of the watched part of `target`'s data.
+pub fn watched_data_hash(target: &AccountInfo, range: Option<DataRange>) -> Result<[u8; 32]> {
+    let data = target.try_borrow_data()?;
+    let watched = match range {
+        Some(range) => range.slice(&data),
+        None => &data,
+    };
+    Ok(hash(watched).to_bytes())
+}
@@ -1,19 +1,28 @@
 // src/instructions/add_metric.rs
 use anchor_lang::prelude::*;
-use crate::state::{MonitoringState, MetricAccount};
+use crate::state::{MonitoringConfig, MonitoringState, MetricAccount, MetricParams};
 use crate::errors::MonitoringError;
+use crate::constants::{MAX_NAME_LENGTH, MAX_RETAINED_PAGES};
 
 #[derive(Accounts)]
 #[instruction(name: String)]
 pub struct AddMetric<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority,
+        realloc = monitoring_state.space() + 32,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
     pub monitoring_state: Account<'info, MonitoringState>,
     
     #[account(
         init,
         payer = authority,
-        space = 8 + std::mem::size_of::<MetricAccount>(),
-        seeds = [b"metric", name.as_bytes()],
+        space = MetricAccount::space(0),
+        seeds = [b"metric", monitoring_state.key().as_ref(), name.as_bytes()],
         bump
     )]
     pub metric_account: Account<'info, MetricAccount>,
@@ -25,11 +34,40 @@ pub struct AddMetric<'info> {
 }
 
 impl<'info> AddMetric<'info> {
-    pub fn validate(&self, name: &str) -> Result<()> {
+    pub fn validate(&self, name: &str, params: &MetricParams) -> Result<()> {
         require!(
-            name.len() <= 32,
+            name.len() <= MAX_NAME_LENGTH,
             MonitoringError::NameTooLong
         );
-        Ok(())
+        require!(
+            self.monitoring_state.metrics.len() < usize::from(self.monitoring_state.config.max_metrics),
+            MonitoringError::MetricLimitReached
+        );
+        validate_metric_params(&self.monitoring_state.config, params)
     }
-}
\ No newline at end of file
+}
+
+/// Checks metric parameters against the monitoring configuration.
+pub fn validate_metric_params(config: &MonitoringConfig, params: &MetricParams) -> Result<()> {
+    require!(
+        params.retention_period <= config.max_data_points,
+        MonitoringError::InvalidMetricParams
+    );
+    require!(
+        params.max_pages == 0 || params.store_history,
+        MonitoringError::InvalidMetricParams
+    );
+    require!(
+        params.max_pages <= MAX_RETAINED_PAGES,
+        MonitoringError::InvalidMaxPages
+    );
+    // Sampling records inline or to a zero-copy history, never to pages
+    require!(
+        params.max_pages == 0 || params.source.is_none(),
+        MonitoringError::InvalidMetricParams
+    );
+    if let (Some(min), Some(max)) = (params.min_value, params.max_value) {
+        require!(min <= max, MonitoringError::InvalidMetricParams);
+    }
+    params.validate()
+}
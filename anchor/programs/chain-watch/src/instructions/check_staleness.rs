@@ -0,0 +1,29 @@
+// src/instructions/check_staleness.rs
+use anchor_lang::prelude::*;
+use crate::state::{AlertConfig, AlertThresholdType, MonitoringState, MetricAccount};
+use crate::errors::MonitoringError;
+
+#[derive(Accounts)]
+pub struct CheckStaleness<'info> {
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(has_one = monitoring_state)]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        constraint = alert_config.metric == metric_account.key() @ MonitoringError::AlertMetricMismatch
+    )]
+    pub alert_config: Account<'info, AlertConfig>,
+}
+
+impl<'info> CheckStaleness<'info> {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.alert_config.params.threshold_type == AlertThresholdType::Stale,
+            MonitoringError::NotStalenessAlert
+        );
+        Ok(())
+    }
+}
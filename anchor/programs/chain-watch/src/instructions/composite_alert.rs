@@ -0,0 +1,420 @@
+// src/instructions/composite_alert.rs
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::{
+    AlertConfig, AlertState, AlertThresholdType, AlertTransition, CompositeAlert,
+    CompositeCondition, CompositeOperator, MonitoringState, MetricAccount, MetricHistory, MetricPage,
+    MetricRollup,
+};
+use crate::events::{CompositeAlertRemoved, CompositeAlertResolved, CompositeAlertTriggered};
+use crate::errors::MonitoringError;
+use crate::constants::MAX_COMPOSITE_CONDITIONS;
+
+#[derive(Accounts)]
+pub struct CreateCompositeAlert<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority,
+        realloc = monitoring_state.space() + 32,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CompositeAlert::INIT_SPACE,
+        seeds = [
+            b"composite",
+            monitoring_state.key().as_ref(),
+            &monitoring_state.next_composite_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub composite_alert: Account<'info, CompositeAlert>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateCompositeAlert<'info> {
+    /// Validates the composite definition. Every referenced alert and metric
+    /// must be supplied in `member_infos` and belong to this monitoring state.
+    /// Returns the metrics the composite depends on, directly or through an
+    /// alert.
+    pub fn validate(
+        &self,
+        operator: CompositeOperator,
+        conditions: &[CompositeCondition],
+        member_infos: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<Pubkey>> {
+        require!(
+            self.monitoring_state.alert_count() < usize::from(self.monitoring_state.config.max_alerts),
+            MonitoringError::AlertLimitReached
+        );
+        require!(
+            !conditions.is_empty() && conditions.len() <= MAX_COMPOSITE_CONDITIONS,
+            MonitoringError::InvalidCompositeAlert
+        );
+        if let CompositeOperator::AtLeast { count } = operator {
+            require!(
+                count > 0 && usize::from(count) <= conditions.len(),
+                MonitoringError::InvalidCompositeAlert
+            );
+        }
+
+        let mut metrics = Vec::new();
+        for condition in conditions {
+            let member_info = member_infos
+                .iter()
+                .find(|info| info.key() == condition.account())
+                .ok_or(MonitoringError::CompositeMemberMissing)?;
+
+            let (member_monitoring_state, metric) = match condition {
+                CompositeCondition::Alert { .. } => {
+                    let alert = Account::<AlertConfig>::try_from(member_info)?;
+                    // Bound to an account watch rather than a metric
+                    require!(
+                        alert.params.threshold_type != AlertThresholdType::AccountChange,
+                        MonitoringError::InvalidCompositeAlert
+                    );
+                    (alert.monitoring_state, alert.metric)
+                }
+                CompositeCondition::Metric {
+                    threshold_type,
+                    threshold_value,
+                    secondary_threshold,
+                    ..
+                } => {
+                    let valid = match threshold_type {
+                        AlertThresholdType::Above | AlertThresholdType::Below => true,
+                        AlertThresholdType::Range => {
+                            secondary_threshold.is_some_and(|upper| upper >= *threshold_value)
+                        }
+                        AlertThresholdType::Change
+                        | AlertThresholdType::Stale
+                        | AlertThresholdType::AccountChange => false,
+                    };
+                    require!(valid, MonitoringError::InvalidCompositeAlert);
+                    let metric = Account::<MetricAccount>::try_from(member_info)?;
+                    (metric.monitoring_state, metric.key())
+                }
+            };
+            require_keys_eq!(
+                member_monitoring_state,
+                self.monitoring_state.key(),
+                MonitoringError::MetricMonitoringMismatch
+            );
+            if !metrics.contains(&metric) {
+                metrics.push(metric);
+            }
+        }
+        Ok(metrics)
+    }
+}
+
+#[derive(Accounts)]
+pub struct EvaluateCompositeAlert<'info> {
+    #[account(
+        seeds = [
+            b"monitoring",
+            monitoring_state.authority.as_ref(),
+            &monitoring_state.namespace.to_le_bytes()
+        ],
+        bump = monitoring_state.bump
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state
+    )]
+    pub composite_alert: Account<'info, CompositeAlert>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCompositeAlert<'info> {
+    #[account(
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state
+    )]
+    pub composite_alert: Account<'info, CompositeAlert>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> UpdateCompositeAlert<'info> {
+    pub fn validate_acknowledge(&self) -> Result<()> {
+        require!(
+            self.composite_alert.state == AlertState::Firing,
+            MonitoringError::AlertNotFiring
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveCompositeAlert<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        close = authority
+    )]
+    pub composite_alert: Account<'info, CompositeAlert>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Alert states and latest metric values available to composite alerts
+#[derive(Default)]
+pub struct ConditionSnapshot {
+    alerts: Vec<(Pubkey, AlertState)>,
+    metrics: Vec<(Pubkey, Option<i64>)>,
+}
+
+impl ConditionSnapshot {
+    pub fn add_alert(&mut self, alert: Pubkey, state: AlertState) {
+        self.alerts.push((alert, state));
+    }
+
+    pub fn add_metric(&mut self, metric: Pubkey, last_value: Option<i64>) {
+        self.metrics.push((metric, last_value));
+    }
+
+    /// Whether `condition` holds, or `None` if its account was not supplied.
+    pub fn resolve(&self, condition: &CompositeCondition) -> Option<bool> {
+        match condition {
+            CompositeCondition::Alert { alert } => self
+                .alerts
+                .iter()
+                .find(|(key, _)| key == alert)
+                .map(|(_, state)| *state == AlertState::Firing),
+            CompositeCondition::Metric { metric, .. } => self
+                .metrics
+                .iter()
+                .find(|(key, _)| key == metric)
+                .map(|(_, last_value)| last_value.is_some_and(|value| condition.is_met_by(value))),
+        }
+    }
+}
+
+/// Kind of a program-owned account, identified by its discriminator
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MonitoringAccountKind {
+    Alert,
+    Metric,
+    Composite,
+    Rollup,
+    Page,
+    History,
+}
+
+impl MonitoringAccountKind {
+    pub fn of(info: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*info.owner, crate::ID, MonitoringError::UnexpectedAccount);
+        let data = info.try_borrow_data()?;
+        let discriminator = data.get(..8).ok_or(MonitoringError::UnexpectedAccount)?;
+
+        if discriminator == AlertConfig::DISCRIMINATOR {
+            Ok(MonitoringAccountKind::Alert)
+        } else if discriminator == MetricAccount::DISCRIMINATOR {
+            Ok(MonitoringAccountKind::Metric)
+        } else if discriminator == CompositeAlert::DISCRIMINATOR {
+            Ok(MonitoringAccountKind::Composite)
+        } else if discriminator == MetricRollup::DISCRIMINATOR {
+            Ok(MonitoringAccountKind::Rollup)
+        } else if discriminator == MetricPage::DISCRIMINATOR {
+            Ok(MonitoringAccountKind::Page)
+        } else if discriminator == MetricHistory::DISCRIMINATOR {
+            Ok(MonitoringAccountKind::History)
+        } else {
+            err!(MonitoringError::UnexpectedAccount)
+        }
+    }
+}
+
+/// The accounts among `infos` of the given kind. Classify accounts before
+/// closing any of them, since closed accounts no longer have a discriminator.
+pub fn accounts_of_kind<'info>(
+    infos: &'info [AccountInfo<'info>],
+    kind: MonitoringAccountKind,
+) -> Result<Vec<&'info AccountInfo<'info>>> {
+    let mut matching = Vec::new();
+    for info in infos {
+        if MonitoringAccountKind::of(info)? == kind {
+            require!(
+                matching.iter().all(|other: &&AccountInfo| other.key != info.key),
+                MonitoringError::DuplicateAccount
+            );
+            matching.push(info);
+        }
+    }
+    Ok(matching)
+}
+
+/// Links a composite alert to (or, with `linked` false, unlinks it from)
+/// each of `metrics`, which must be supplied writable in `metric_infos`.
+pub fn update_composite_counts<'info>(
+    metrics: &[Pubkey],
+    metric_infos: &[&'info AccountInfo<'info>],
+    linked: bool,
+) -> Result<()> {
+    for key in metrics {
+        let info = metric_infos
+            .iter()
+            .find(|info| info.key == key)
+            .ok_or(MonitoringError::CompositeMemberMissing)?;
+        require!(info.is_writable, MonitoringError::MetricNotWritable);
+
+        let mut metric = Account::<MetricAccount>::try_from(info)?;
+        metric.composite_count = if linked {
+            metric
+                .composite_count
+                .checked_add(1)
+                .ok_or(MonitoringError::ArithmeticOverflow)?
+        } else {
+            metric.composite_count.saturating_sub(1)
+        };
+        metric.exit(&crate::ID)?;
+    }
+    Ok(())
+}
+
+/// Closes the composite alerts depending on `metric` that `closes`
+/// selects, refunding rent to `authority`, and unlinks them from the other
+/// metrics they depend on. Every composite alert depending on `metric` must
+/// be in `composite_infos`, and every other metric a closed one depends on
+/// in `metric_infos`. The caller unlinks `metric` itself. Returns the keys
+/// of the closed composite alerts.
+pub fn close_dependent_composites<'info>(
+    monitoring_state: &mut Account<'info, MonitoringState>,
+    metric: &Account<'info, MetricAccount>,
+    composite_infos: &[&'info AccountInfo<'info>],
+    metric_infos: &[&'info AccountInfo<'info>],
+    authority: &AccountInfo<'info>,
+    closes: impl Fn(&CompositeAlert) -> bool,
+) -> Result<Vec<Pubkey>> {
+    require!(
+        composite_infos.len() == usize::from(metric.composite_count),
+        MonitoringError::MissingMetricComposites
+    );
+
+    let metric_key = metric.key();
+    let mut closed = Vec::new();
+    for composite_info in composite_infos {
+        require!(composite_info.is_writable, MonitoringError::AlertNotWritable);
+        let composite = Account::<CompositeAlert>::try_from(composite_info)?;
+        require!(
+            composite.metrics.contains(&metric_key),
+            MonitoringError::UnexpectedAccount
+        );
+        if !closes(&composite) {
+            continue;
+        }
+
+        let others: Vec<Pubkey> = composite
+            .metrics
+            .iter()
+            .copied()
+            .filter(|key| *key != metric_key)
+            .collect();
+        update_composite_counts(&others, metric_infos, false)?;
+        composite.close(authority.clone())?;
+
+        let key = composite_info.key();
+        monitoring_state.composite_alerts.retain(|other| *other != key);
+        emit!(CompositeAlertRemoved {
+            monitoring_state: monitoring_state.key(),
+            composite_alert: key,
+        });
+        closed.push(key);
+    }
+    Ok(closed)
+}
+
+/// Evaluates composite alerts against `snapshot` and persists their state.
+/// Returns the keys of the composite alerts that fired.
+pub fn evaluate_composites<'info>(
+    monitoring_state: &Account<'info, MonitoringState>,
+    composite_infos: &[&'info AccountInfo<'info>],
+    snapshot: &ConditionSnapshot,
+    now: i64,
+) -> Result<Vec<Pubkey>> {
+    let mut fired = Vec::new();
+
+    for composite_info in composite_infos {
+        require!(composite_info.is_writable, MonitoringError::AlertNotWritable);
+        let mut composite = Account::<CompositeAlert>::try_from(composite_info)?;
+        require_keys_eq!(
+            composite.monitoring_state,
+            monitoring_state.key(),
+            MonitoringError::MetricMonitoringMismatch
+        );
+
+        if evaluate_composite(&mut composite, snapshot, monitoring_state, now)? {
+            fired.push(composite.key());
+        }
+        composite.exit(&crate::ID)?;
+    }
+
+    Ok(fired)
+}
+
+/// Evaluates a single composite alert against `snapshot` and emits its
+/// transition. It cannot start firing while any metric it depends on is
+/// in a maintenance window. Returns whether it fired.
+pub fn evaluate_composite(
+    composite: &mut Account<CompositeAlert>,
+    snapshot: &ConditionSnapshot,
+    monitoring_state: &MonitoringState,
+    now: i64,
+) -> Result<bool> {
+    let satisfied = composite
+        .is_satisfied(|condition| snapshot.resolve(condition))
+        .ok_or(MonitoringError::CompositeMemberMissing)?;
+    let in_maintenance = composite
+        .metrics
+        .iter()
+        .any(|metric| monitoring_state.in_maintenance(metric, now));
+
+    match composite.advance(satisfied, now, in_maintenance) {
+        AlertTransition::Triggered => {
+            msg!("Composite alert {} triggered", composite.key());
+            emit!(CompositeAlertTriggered {
+                composite_alert: composite.key(),
+                triggered_at: now,
+            });
+            Ok(true)
+        }
+        AlertTransition::Resolved => {
+            emit!(CompositeAlertResolved {
+                composite_alert: composite.key(),
+                firing_since: composite.firing_since,
+                resolved_at: now,
+            });
+            Ok(false)
+        }
+        AlertTransition::Unchanged => Ok(false),
+    }
+}
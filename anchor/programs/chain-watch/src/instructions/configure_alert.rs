@@ -1,25 +1,83 @@
 // src/instructions/configure_alert.rs
 use anchor_lang::prelude::*;
-use crate::state::{AlertConfig, MonitoringState, MetricAccount};
+use crate::state::{AlertConfig, AlertConfigParams, AlertThresholdType, MonitoringState, MetricAccount};
+use crate::errors::MonitoringError;
+use crate::constants::MAX_WEBHOOK_URL_LENGTH;
 
 #[derive(Accounts)]
 pub struct ConfigureAlert<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority,
+        realloc = monitoring_state.space() + 32,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
     pub monitoring_state: Account<'info, MonitoringState>,
 
     #[account(
         init,
         payer = authority,
-        space = 8 + std::mem::size_of::<AlertConfig>(),
-        seeds = [b"alert", metric_account.key().as_ref()],
+        space = 8 + AlertConfig::INIT_SPACE,
+        seeds = [
+            b"alert",
+            monitoring_state.key().as_ref(),
+            metric_account.key().as_ref(),
+            &metric_account.next_alert_id.to_le_bytes()
+        ],
         bump
     )]
     pub alert_config: Account<'info, AlertConfig>,
 
+    #[account(
+        mut,
+        has_one = monitoring_state
+    )]
     pub metric_account: Account<'info, MetricAccount>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+}
+
+impl<'info> ConfigureAlert<'info> {
+    pub fn validate(&self, params: &AlertConfigParams) -> Result<()> {
+        require!(
+            self.monitoring_state.alert_count() < usize::from(self.monitoring_state.config.max_alerts),
+            MonitoringError::AlertLimitReached
+        );
+        // Account change alerts are created together with their watch
+        require!(
+            params.threshold_type != AlertThresholdType::AccountChange,
+            MonitoringError::InvalidAlertConfig
+        );
+        validate_alert_params(params)
+    }
+}
+
+/// Checks that alert parameters are internally consistent.
+pub fn validate_alert_params(params: &AlertConfigParams) -> Result<()> {
+    require!(
+        params.webhook_url.as_ref().map_or(0, String::len) <= MAX_WEBHOOK_URL_LENGTH,
+        MonitoringError::InvalidAlertConfig
+    );
+    require!(params.hysteresis >= 0, MonitoringError::InvalidAlertConfig);
+    if params.threshold_type == AlertThresholdType::Stale {
+        require!(params.threshold_value > 0, MonitoringError::InvalidAlertConfig);
+    }
+    if params.threshold_type == AlertThresholdType::Range {
+        // The hysteresis band must leave room for a value to recover
+        require!(
+            params.secondary_threshold.is_some_and(|upper| {
+                params.threshold_value.saturating_add(params.hysteresis)
+                    <= upper.saturating_sub(params.hysteresis)
+            }),
+            MonitoringError::InvalidAlertConfig
+        );
+    }
+    Ok(())
+}
+
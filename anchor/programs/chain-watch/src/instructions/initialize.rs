@@ -3,12 +3,13 @@ use anchor_lang::prelude::*;
 use crate::state::MonitoringState;
 
 #[derive(Accounts)]
+#[instruction(namespace: u64)]
 pub struct InitializeMonitoring<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + std::mem::size_of::<MonitoringState>(),
-        seeds = [b"monitoring"],
+        space = MonitoringState::BASE_SPACE,
+        seeds = [b"monitoring", authority.key().as_ref(), &namespace.to_le_bytes()],
         bump
     )]
     pub monitoring_state: Account<'info, MonitoringState>,
@@ -17,4 +18,17 @@ pub struct InitializeMonitoring<'info> {
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,35 @@
+// src/instructions/initialize_history.rs
+use anchor_lang::prelude::*;
+use crate::state::{MetricAccount, MetricHistory};
+use crate::errors::MonitoringError;
+
+#[derive(Accounts)]
+pub struct InitializeMetricHistory<'info> {
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(zero)]
+    pub metric_history: AccountLoader<'info, MetricHistory>,
+
+    /// Receives the rent freed by moving inline data points into the history
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+impl<'info> InitializeMetricHistory<'info> {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.metric_account.history.is_none(),
+            MonitoringError::HistoryAlreadyAttached
+        );
+        // Attaching a history would strand the metric's pages
+        require!(
+            self.metric_account.params.max_pages == 0,
+            MonitoringError::MetricPageMismatch
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+// src/instructions/maintenance.rs
+use anchor_lang::prelude::*;
+use crate::state::{MaintenanceWindow, MonitoringState};
+use crate::errors::MonitoringError;
+use crate::constants::MAX_MAINTENANCE_WINDOWS;
+
+#[derive(Accounts)]
+pub struct AddMaintenanceWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority,
+        realloc = monitoring_state.space() + MaintenanceWindow::INIT_SPACE,
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageMaintenanceWindows<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> AddMaintenanceWindow<'info> {
+    pub fn validate(&self, start: i64, end: i64, now: i64) -> Result<()> {
+        require!(
+            start < end && end > now,
+            MonitoringError::InvalidMaintenanceWindow
+        );
+        require!(
+            self.monitoring_state.maintenance_windows.len() < MAX_MAINTENANCE_WINDOWS,
+            MonitoringError::MaintenanceWindowLimitReached
+        );
+        Ok(())
+    }
+}
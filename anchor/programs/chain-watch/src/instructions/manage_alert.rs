@@ -0,0 +1,94 @@
+// src/instructions/manage_alert.rs
+use anchor_lang::prelude::*;
+use crate::state::{
+    AlertConfig, AlertConfigParams, AlertState, AlertThresholdType, AlertTransition,
+    MonitoringState, MetricAccount,
+};
+use crate::events::AlertResolved;
+use super::validate_alert_params;
+use crate::errors::MonitoringError;
+
+#[derive(Accounts)]
+pub struct UpdateAlert<'info> {
+    #[account(
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state
+    )]
+    pub alert_config: Account<'info, AlertConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> UpdateAlert<'info> {
+    /// Checks new parameters. An alert cannot be switched into or out of
+    /// `AccountChange`, since those are bound to an account watch.
+    pub fn validate(&self, params: &AlertConfigParams) -> Result<()> {
+        let current = &self.alert_config.params.threshold_type;
+        require!(
+            (*current == AlertThresholdType::AccountChange)
+                == (params.threshold_type == AlertThresholdType::AccountChange),
+            MonitoringError::InvalidAlertConfig
+        );
+        validate_alert_params(params)
+    }
+
+    pub fn validate_acknowledge(&self) -> Result<()> {
+        require!(
+            self.alert_config.state == AlertState::Firing,
+            MonitoringError::AlertNotFiring
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveAlert<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        constraint = alert_config.metric == metric_account.key(),
+        close = authority
+    )]
+    pub alert_config: Account<'info, AlertConfig>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// Resets an alert whose configuration or status changed, emitting
+/// `AlertResolved` if it was firing. No value resolved it, so the event
+/// carries none.
+pub fn reset_alert(alert: &mut Account<AlertConfig>, now: i64) {
+    if alert.reset(now) == AlertTransition::Resolved {
+        emit!(AlertResolved {
+            alert: alert.key(),
+            metric: alert.metric,
+            value: 0,
+            scale: 0,
+            unit: String::new(),
+            firing_since: alert.firing_since,
+            resolved_at: now,
+        });
+    }
+}
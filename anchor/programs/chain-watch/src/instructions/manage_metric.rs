@@ -0,0 +1,157 @@
+// src/instructions/manage_metric.rs
+use anchor_lang::prelude::*;
+use crate::state::{
+    AlertConfig, MonitoringState, MetricAccount, MetricHistory, MetricPage, MetricParams,
+    MetricRollup,
+};
+use crate::events::{MetricPageClosed, MetricRollupClosed};
+use crate::errors::MonitoringError;
+use super::validate_metric_params;
+
+#[derive(Accounts)]
+pub struct UpdateMetric<'info> {
+    #[account(
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> UpdateMetric<'info> {
+    pub fn validate(&self, params: &MetricParams) -> Result<()> {
+        validate_metric_params(&self.monitoring_state.config, params)?;
+
+        // The sample interval of a source may still be tuned
+        let source_of = |params: &MetricParams| {
+            params.source.as_ref().map(|source| (source.account, source.field))
+        };
+        let current = &self.metric_account.params;
+        require!(
+            params.kind == current.kind
+                && source_of(params) == source_of(current)
+                && params.max_pages == current.max_pages
+                && params.rollups == current.rollups,
+            MonitoringError::ImmutableMetricParam
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveMetric<'info> {
+    #[account(
+        mut,
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        close = authority
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(
+        mut,
+        close = authority
+    )]
+    pub metric_history: Option<AccountLoader<'info, MetricHistory>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+impl<'info> RemoveMetric<'info> {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.metric_account.history
+                == self.metric_history.as_ref().map(|history| history.key()),
+            MonitoringError::HistoryAccountMismatch
+        );
+        Ok(())
+    }
+
+    /// Closes every alert bound to the metric, refunding rent to the
+    /// authority. All of the metric's alerts must be supplied.
+    pub fn close_alerts(&self, alert_infos: &[&'info AccountInfo<'info>]) -> Result<Vec<Pubkey>> {
+        require!(
+            alert_infos.len() == usize::from(self.metric_account.alert_count),
+            MonitoringError::MissingMetricAlerts
+        );
+
+        let mut closed = Vec::with_capacity(alert_infos.len());
+        for alert_info in alert_infos {
+            require!(alert_info.is_writable, MonitoringError::AlertNotWritable);
+            let alert = Account::<AlertConfig>::try_from(alert_info)?;
+            require_keys_eq!(
+                alert.metric,
+                self.metric_account.key(),
+                MonitoringError::AlertMetricMismatch
+            );
+
+            alert.close(self.authority.to_account_info())?;
+            closed.push(alert_info.key());
+        }
+
+        Ok(closed)
+    }
+
+    /// Closes every history page of the metric, refunding rent to the
+    /// authority. All of the metric's open pages must be supplied.
+    pub fn close_pages(&self, page_infos: &[&'info AccountInfo<'info>]) -> Result<()> {
+        let metric = &self.metric_account;
+        require!(
+            page_infos.len() as u64 == metric.existing_pages(),
+            MonitoringError::MetricPageMismatch
+        );
+
+        for page_info in page_infos {
+            require!(page_info.is_writable, MonitoringError::MetricPageMismatch);
+            let page = Account::<MetricPage>::try_from(page_info)?;
+            require_keys_eq!(page.metric, metric.key(), MonitoringError::MetricPageMismatch);
+
+            emit!(MetricPageClosed {
+                metric: metric.key(),
+                metric_page: page_info.key(),
+                page_index: page.page_index,
+            });
+            page.close(self.authority.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /// Closes every rollup of the metric, refunding rent to the authority.
+    /// All of the metric's rollups must be supplied.
+    pub fn close_rollups(&self, rollup_infos: &[&'info AccountInfo<'info>]) -> Result<()> {
+        let metric = &self.metric_account;
+        require!(
+            rollup_infos.len() == usize::from(metric.rollup_count),
+            MonitoringError::RollupTierMismatch
+        );
+
+        for rollup_info in rollup_infos {
+            require!(rollup_info.is_writable, MonitoringError::RollupNotWritable);
+            let rollup = Account::<MetricRollup>::try_from(rollup_info)?;
+            require_keys_eq!(rollup.metric, metric.key(), MonitoringError::RollupTierMismatch);
+
+            emit!(MetricRollupClosed {
+                metric: metric.key(),
+                metric_rollup: rollup_info.key(),
+            });
+            rollup.close(self.authority.to_account_info())?;
+        }
+        Ok(())
+    }
+}
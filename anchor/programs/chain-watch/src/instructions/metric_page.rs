@@ -0,0 +1,42 @@
+// src/instructions/metric_page.rs
+use anchor_lang::prelude::*;
+use crate::state::{MetricAccount, MetricPage};
+use crate::errors::MonitoringError;
+
+#[derive(Accounts)]
+pub struct CloseMetricPage<'info> {
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"metric_page",
+            metric_account.key().as_ref(),
+            &metric_account.oldest_page.to_le_bytes()
+        ],
+        bump,
+        close = authority
+    )]
+    pub metric_page: Account<'info, MetricPage>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+impl<'info> CloseMetricPage<'info> {
+    /// Only the oldest page may be closed, and only while more pages are
+    /// open than `max_pages` retains. The page being written is never closed.
+    pub fn validate(&self) -> Result<()> {
+        let metric = &self.metric_account;
+        require!(
+            metric.oldest_page < metric.current_page
+                && metric.open_pages() > u64::from(metric.params.max_pages),
+            MonitoringError::MetricPageRetained
+        );
+        Ok(())
+    }
+}
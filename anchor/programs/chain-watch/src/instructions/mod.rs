@@ -3,8 +3,38 @@ mod initialize;
 mod add_metric;
 mod configure_alert;
 mod record_metric;
+mod initialize_history;
+mod record_metrics_batch;
+mod manage_metric;
+mod manage_alert;
+mod maintenance;
+mod composite_alert;
+mod check_staleness;
+mod sample_metric;
+mod sample_token_metric;
+mod program_watch;
+mod account_watch;
+mod metric_page;
+mod rollup;
+mod prune_metric;
+mod resize;
 
 pub use initialize::*;
 pub use add_metric::*;
 pub use configure_alert::*;
-pub use record_metric::*;
\ No newline at end of file
+pub use record_metric::*;
+pub use initialize_history::*;
+pub use record_metrics_batch::*;
+pub use manage_metric::*;
+pub use manage_alert::*;
+pub use maintenance::*;
+pub use composite_alert::*;
+pub use check_staleness::*;
+pub use sample_metric::*;
+pub use sample_token_metric::*;
+pub use program_watch::*;
+pub use account_watch::*;
+pub use metric_page::*;
+pub use rollup::*;
+pub use prune_metric::*;
+pub use resize::*;
@@ -0,0 +1,85 @@
+// src/instructions/program_watch.rs
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use anchor_lang::solana_program::hash::hash;
+use crate::state::{MonitoringState, ProgramWatch};
+use crate::errors::MonitoringError;
+use crate::constants::MAX_CODE_HASH_LEN;
+
+#[derive(Accounts)]
+pub struct WatchProgram<'info> {
+    #[account(
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProgramWatch::INIT_SPACE,
+        seeds = [b"program_watch", monitoring_state.key().as_ref(), program.key().as_ref()],
+        bump
+    )]
+    pub program_watch: Account<'info, ProgramWatch>,
+
+    /// CHECK: only its key is used, to derive the `ProgramData` address
+    #[account(executable)]
+    pub program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [program.key().as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckProgramWatch<'info> {
+    #[account(
+        mut,
+        has_one = program_data @ MonitoringError::ProgramDataMismatch
+    )]
+    pub program_watch: Account<'info, ProgramWatch>,
+
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveProgramWatch<'info> {
+    #[account(
+        seeds = [b"monitoring", authority.key().as_ref(), &monitoring_state.namespace.to_le_bytes()],
+        bump = monitoring_state.bump,
+        has_one = authority
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        close = authority
+    )]
+    pub program_watch: Account<'info, ProgramWatch>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+/// SHA-256 hash of the first `MAX_CODE_HASH_LEN` executable bytes stored
+/// after the `ProgramData` header. Every deployment also moves the
+/// deployment slot, so redeploys that only change later bytes are still
+/// detected.
+pub fn executable_hash(program_data: &AccountInfo) -> Result<[u8; 32]> {
+    let data = program_data.try_borrow_data()?;
+    let code = data
+        .get(UpgradeableLoaderState::size_of_programdata_metadata()..)
+        .ok_or(MonitoringError::ProgramDataMismatch)?;
+    Ok(hash(&code[..code.len().min(MAX_CODE_HASH_LEN)]).to_bytes())
+}
@@ -0,0 +1,67 @@
+// src/instructions/prune_metric.rs
+use anchor_lang::prelude::*;
+use crate::state::{MetricAccount, MetricHistory, MetricPage};
+use crate::events::MetricPageClosed;
+use crate::errors::MonitoringError;
+
+#[derive(Accounts)]
+pub struct PruneMetric<'info> {
+    #[account(
+        mut,
+        has_one = authority @ MonitoringError::Unauthorized
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(mut)]
+    pub metric_history: Option<AccountLoader<'info, MetricHistory>>,
+
+    /// Receives the rent freed by shrinking the metric account
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+}
+
+impl<'info> PruneMetric<'info> {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.metric_account.params.ttl_seconds > 0,
+            MonitoringError::MetricHasNoTtl
+        );
+        require!(
+            self.metric_account.history
+                == self.metric_history.as_ref().map(|history| history.key()),
+            MonitoringError::HistoryAccountMismatch
+        );
+        Ok(())
+    }
+
+    /// Closes the expired history pages in `page_infos`, oldest first,
+    /// refunding rent to the authority. Returns how many points they held.
+    pub fn close_expired_pages(
+        &mut self,
+        page_infos: &'info [AccountInfo<'info>],
+        now: i64,
+    ) -> Result<usize> {
+        let mut removed = 0;
+        for page_info in page_infos {
+            require!(page_info.is_writable, MonitoringError::MetricPageMismatch);
+            let page = Account::<MetricPage>::try_from(page_info)?;
+            let metric = &mut self.metric_account;
+            require_keys_eq!(page.metric, metric.key(), MonitoringError::MetricPageMismatch);
+            require!(
+                page.page_index == metric.oldest_page,
+                MonitoringError::MetricPageMismatch
+            );
+            require!(metric.page_expired(&page, now), MonitoringError::MetricPageRetained);
+
+            metric.close_oldest_page(&page);
+            removed += page.points.len();
+            emit!(MetricPageClosed {
+                metric: metric.key(),
+                metric_page: page_info.key(),
+                page_index: page.page_index,
+            });
+            page.close(self.authority.to_account_info())?;
+        }
+        Ok(removed)
+    }
+}
@@ -1,11 +1,262 @@
 // programs/chain-watch/src/instructions/record_metric.rs
 use anchor_lang::prelude::*;
-use crate::state::MetricAccount;
-  // Updated error enum name
+use crate::state::{
+    AlertConfig, AlertTransition, CompositeAlert, MonitoringConfig, MonitoringState, MetricAccount,
+    MetricHistory, MetricPage, RecordOutcome,
+};
+use crate::events::{AlertResolved, AlertTriggered, MetricPointDeduplicated};
+use super::{evaluate_composites, ConditionSnapshot, MonitoringAccountKind};
+use crate::errors::MonitoringError;
 
 #[derive(Accounts)]
+#[instruction(timestamp: i64)]
 pub struct RecordMetric<'info> {
-    #[account(mut)]
+    #[account(
+        seeds = [
+            b"monitoring",
+            monitoring_state.authority.as_ref(),
+            &monitoring_state.namespace.to_le_bytes()
+        ],
+        bump = monitoring_state.bump
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        has_one = authority,
+        realloc = metric_account.space_after_record(monitoring_state.config.max_data_points),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
     pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(mut)]
+    pub metric_history: Option<AccountLoader<'info, MetricHistory>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MetricPage::INIT_SPACE,
+        seeds = [
+            b"metric_page",
+            metric_account.key().as_ref(),
+            &metric_account.page_index_for(timestamp).to_le_bytes()
+        ],
+        bump
+    )]
+    pub metric_page: Option<Account<'info, MetricPage>>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
-}
\ No newline at end of file
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RecordMetric<'info> {
+    pub fn validate(&self, timestamp: i64) -> Result<()> {
+        require!(
+            self.metric_account.params.source.is_none(),
+            MonitoringError::SourcedMetric
+        );
+        require!(
+            self.metric_account.history
+                == self.metric_history.as_ref().map(|history| history.key()),
+            MonitoringError::HistoryAccountMismatch
+        );
+        require!(
+            self.metric_account.is_paged() == self.metric_page.is_some(),
+            MonitoringError::MetricPageMismatch
+        );
+        validate_update(
+            &self.monitoring_state.config,
+            &self.metric_account,
+            timestamp,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+}
+
+/// Checks a data point timestamp against the cluster clock and the
+/// monitoring state's rate limit. New points are spaced by their
+/// timestamps, and late points by the cluster time since the last late one.
+pub fn validate_update(
+    config: &MonitoringConfig,
+    metric: &MetricAccount,
+    timestamp: i64,
+    now: i64,
+) -> Result<()> {
+    require!(timestamp <= now, MonitoringError::TimestampInFuture);
+
+    // Rate limiting spaces recorded points, so the first one is never limited
+    if config.rate_limiting_enabled && metric.aggregates.count > 0 {
+        let elapsed = if metric.is_late(timestamp) {
+            now.saturating_sub(metric.late_recorded_at)
+        } else {
+            timestamp.saturating_sub(metric.last_updated)
+        };
+        require!(
+            elapsed >= config.min_update_interval,
+            MonitoringError::UpdateTooFrequent
+        );
+    }
+    Ok(())
+}
+
+/// Records a data point to the metric's zero-copy history if attached,
+/// to the history page covering it if paged, otherwise to its inline data
+/// points. Duplicate timestamps are reported with `MetricPointDeduplicated`
+/// and leave the metric untouched.
+pub fn record_point(
+    metric_account: &mut Account<MetricAccount>,
+    metric_history: Option<&AccountLoader<MetricHistory>>,
+    metric_page: Option<&mut Account<MetricPage>>,
+    max_data_points: u32,
+    timestamp: i64,
+    value: i64,
+) -> Result<RecordOutcome> {
+    let outcome = match (metric_history, metric_page) {
+        (Some(metric_history), _) => {
+            let mut history = metric_history.load_mut()?;
+            metric_account.record_to_history(&mut history, timestamp, value)
+        }
+        (None, Some(metric_page)) => {
+            // Freshly created by `init_if_needed`
+            if metric_page.metric == Pubkey::default() {
+                metric_page.metric = metric_account.key();
+                metric_page.page_index = metric_account.page_index_for(timestamp);
+            }
+            metric_account.record_to_page(metric_page, timestamp, value)
+        }
+        (None, None) => metric_account.record(timestamp, value, max_data_points),
+    }?;
+
+    if outcome == RecordOutcome::Duplicate {
+        emit!(MetricPointDeduplicated {
+            metric: metric_account.key(),
+            timestamp,
+            value,
+        });
+    }
+    Ok(outcome)
+}
+
+/// Evaluates the alerts passed alongside a newly recorded value and
+/// persists their updated state. Late values, recorded with `outcome`
+/// `Inserted`, are evaluated with `AlertConfig::evaluate_late` against the
+/// point retained before them.
+///
+/// `infos` must hold every alert bound to `metric`, which are evaluated,
+/// and every composite alert depending on it, which are evaluated
+/// afterwards. Other alerts and metrics are only read as inputs to the
+/// composite alerts, and rollups are skipped. Returns the keys of the
+/// alerts and composite alerts that fired.
+pub fn evaluate_alerts<'info>(
+    monitoring_state: &Account<'info, MonitoringState>,
+    metric_account: &Account<'info, MetricAccount>,
+    infos: &'info [AccountInfo<'info>],
+    value: i64,
+    previous: Option<i64>,
+    outcome: RecordOutcome,
+) -> Result<Vec<Pubkey>> {
+    for (i, info) in infos.iter().enumerate() {
+        require!(
+            infos[..i].iter().all(|other| other.key != info.key),
+            MonitoringError::DuplicateAccount
+        );
+    }
+
+    let metric = metric_account.key();
+    let params = &metric_account.params;
+    let now = Clock::get()?.unix_timestamp;
+    let in_maintenance = monitoring_state.in_maintenance(&metric, now);
+    let mut fired = Vec::new();
+    let mut snapshot = ConditionSnapshot::default();
+    let mut composite_infos = Vec::new();
+    let mut evaluated = 0;
+    let mut dependent_composites = 0;
+    // Composite alerts follow the latest value, even after a late one
+    snapshot.add_metric(metric, metric_account.last_value());
+
+    for info in infos {
+        match MonitoringAccountKind::of(info)? {
+            MonitoringAccountKind::Alert => {}
+            MonitoringAccountKind::Metric => {
+                let member = Account::<MetricAccount>::try_from(info)?;
+                snapshot.add_metric(member.key(), member.last_value());
+                continue;
+            }
+            MonitoringAccountKind::Composite => {
+                if Account::<CompositeAlert>::try_from(info)?.metrics.contains(&metric) {
+                    dependent_composites += 1;
+                }
+                composite_infos.push(info);
+                continue;
+            }
+            // Already folded by `apply_rollups`, or recorded to by a batch
+            MonitoringAccountKind::Rollup | MonitoringAccountKind::History => continue,
+            MonitoringAccountKind::Page => return err!(MonitoringError::UnexpectedAccount),
+        }
+
+        let mut alert = Account::<AlertConfig>::try_from(info)?;
+        if alert.metric != metric {
+            snapshot.add_alert(alert.key(), alert.state);
+            continue;
+        }
+        require!(info.is_writable, MonitoringError::AlertNotWritable);
+        evaluated += 1;
+
+        let transition = match outcome {
+            RecordOutcome::Inserted { previous } => {
+                alert.evaluate_late(value, previous, now, in_maintenance)
+            }
+            _ => alert.evaluate(value, previous, now, in_maintenance),
+        };
+        match transition {
+            AlertTransition::Triggered => {
+                msg!("Alert {} triggered", alert.key());
+                emit!(AlertTriggered {
+                    alert: alert.key(),
+                    metric,
+                    value,
+                    scale: params.scale,
+                    unit: params.unit.clone(),
+                    consecutive_violations: alert.consecutive_violations,
+                    triggered_at: now,
+                });
+                fired.push(alert.key());
+            }
+            AlertTransition::Resolved => {
+                emit!(AlertResolved {
+                    alert: alert.key(),
+                    metric,
+                    value,
+                    scale: params.scale,
+                    unit: params.unit.clone(),
+                    firing_since: alert.firing_since,
+                    resolved_at: now,
+                });
+            }
+            AlertTransition::Unchanged => {}
+        }
+        snapshot.add_alert(alert.key(), alert.state);
+        alert.exit(&crate::ID)?;
+    }
+
+    require!(
+        evaluated == usize::from(metric_account.alert_count),
+        MonitoringError::MissingMetricAlerts
+    );
+    require!(
+        dependent_composites == usize::from(metric_account.composite_count),
+        MonitoringError::MissingMetricComposites
+    );
+
+    fired.extend(evaluate_composites(
+        monitoring_state,
+        &composite_infos,
+        &snapshot,
+        now,
+    )?);
+    Ok(fired)
+}
@@ -0,0 +1,112 @@
+// src/instructions/record_metrics_batch.rs
+use anchor_lang::prelude::*;
+use crate::state::{MonitoringConfig, MonitoringState, MetricAccount, MetricHistory};
+use crate::errors::MonitoringError;
+use super::{validate_update, MonitoringAccountKind};
+
+#[derive(Accounts)]
+pub struct RecordMetricsBatch<'info> {
+    #[account(
+        seeds = [
+            b"monitoring",
+            monitoring_state.authority.as_ref(),
+            &monitoring_state.namespace.to_le_bytes()
+        ],
+        bump = monitoring_state.bump
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// A single data point in a batch, referencing a metric account by its
+/// index among the metric accounts in `remaining_accounts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchRecordEntry {
+    pub metric_index: u8,
+    pub timestamp: i64,
+    pub value: i64,
+}
+
+/// A batch entry that was rejected and not recorded
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchEntryFailure {
+    /// Position of the entry in the submitted batch
+    pub entry_index: u16,
+    /// Program error code the entry was rejected with
+    pub error_code: u64,
+}
+
+impl<'info> RecordMetricsBatch<'info> {
+    /// Loads the metric accounts referenced by a batch, checking that each
+    /// belongs to this monitoring state and is updatable by the signer.
+    /// Alerts, composite alerts and other accounts among `infos` are left
+    /// for alert evaluation.
+    pub fn load_metrics(
+        &self,
+        infos: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<Account<'info, MetricAccount>>> {
+        let mut metrics: Vec<Account<'info, MetricAccount>> = Vec::new();
+
+        for metric_info in infos {
+            if MonitoringAccountKind::of(metric_info)? != MonitoringAccountKind::Metric {
+                continue;
+            }
+            require!(metric_info.is_writable, MonitoringError::MetricNotWritable);
+            require!(
+                metrics.iter().all(|metric| metric.key() != metric_info.key()),
+                MonitoringError::DuplicateBatchMetric
+            );
+
+            let metric = Account::<MetricAccount>::try_from(metric_info)?;
+            require_keys_eq!(
+                metric.monitoring_state,
+                self.monitoring_state.key(),
+                MonitoringError::MetricMonitoringMismatch
+            );
+            require_keys_eq!(
+                metric.authority,
+                self.authority.key(),
+                MonitoringError::Unauthorized
+            );
+
+            metrics.push(metric);
+        }
+
+        Ok(metrics)
+    }
+}
+
+/// The zero-copy history attached to `metric`, if any, which must then be
+/// among `infos`.
+pub fn batch_history<'info>(
+    infos: &'info [AccountInfo<'info>],
+    metric: &MetricAccount,
+) -> Result<Option<AccountLoader<'info, MetricHistory>>> {
+    let Some(history) = metric.history else {
+        return Ok(None);
+    };
+    let info = infos
+        .iter()
+        .find(|info| *info.key == history)
+        .ok_or(MonitoringError::HistoryAccountMismatch)?;
+    require!(info.is_writable, MonitoringError::HistoryAccountMismatch);
+    AccountLoader::try_from(info).map(Some)
+}
+
+/// Checks a batch entry before it is recorded. Sourced metrics are only
+/// sampled, and paged metrics need their page created by `record_metric`,
+/// so their entries are rejected.
+pub fn validate_batch_entry(
+    config: &MonitoringConfig,
+    metric: &MetricAccount,
+    timestamp: i64,
+    now: i64,
+) -> Result<()> {
+    require!(metric.params.source.is_none(), MonitoringError::SourcedMetric);
+    require!(!metric.is_paged(), MonitoringError::MetricPageMismatch);
+    validate_update(config, metric, timestamp, now)
+}
@@ -0,0 +1,54 @@
+// src/instructions/resize.rs
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+/// Grows `account` to `new_len` bytes if it is smaller, with `payer`
+/// topping up the rent-exempt balance.
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    if new_len <= account.data_len() {
+        return Ok(());
+    }
+
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(new_len, false)?;
+    Ok(())
+}
+
+/// Shrinks `account` to `new_len` bytes if it is larger and moves the rent
+/// no longer needed to `recipient`. Returns the refunded lamports.
+pub fn shrink_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    recipient: &AccountInfo<'info>,
+) -> Result<u64> {
+    if new_len >= account.data_len() {
+        return Ok(0);
+    }
+    account.realloc(new_len, false)?;
+
+    let refund = account
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(new_len));
+    **account.try_borrow_mut_lamports()? -= refund;
+    **recipient.try_borrow_mut_lamports()? += refund;
+    Ok(refund)
+}
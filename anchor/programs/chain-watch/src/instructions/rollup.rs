@@ -0,0 +1,84 @@
+// src/instructions/rollup.rs
+use anchor_lang::prelude::*;
+use crate::state::{MetricAccount, MetricRollup, RollupInterval};
+use crate::errors::MonitoringError;
+use super::MonitoringAccountKind;
+
+#[derive(Accounts)]
+#[instruction(interval: RollupInterval)]
+pub struct InitializeRollup<'info> {
+    #[account(
+        mut,
+        has_one = authority
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MetricRollup::INIT_SPACE,
+        seeds = [b"rollup", metric_account.key().as_ref(), &interval.seconds().to_le_bytes()],
+        bump
+    )]
+    pub metric_rollup: Account<'info, MetricRollup>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeRollup<'info> {
+    pub fn validate(&self, interval: RollupInterval) -> Result<()> {
+        require!(
+            self.metric_account
+                .params
+                .rollups
+                .iter()
+                .any(|tier| tier.interval == interval),
+            MonitoringError::RollupTierMismatch
+        );
+        Ok(())
+    }
+}
+
+/// Folds a recorded point into every rollup tier of `metric`. Each
+/// configured tier's `MetricRollup` must be among `infos`; rollups of other
+/// metrics and other accounts are left for their own metric and for alert
+/// evaluation.
+pub fn apply_rollups<'info>(
+    metric: &Account<'info, MetricAccount>,
+    infos: &'info [AccountInfo<'info>],
+    timestamp: i64,
+    value: i64,
+) -> Result<()> {
+    let mut applied = 0;
+
+    for info in infos {
+        if MonitoringAccountKind::of(info)? != MonitoringAccountKind::Rollup {
+            continue;
+        }
+        let mut rollup = Account::<MetricRollup>::try_from(info)?;
+        if rollup.metric != metric.key() {
+            continue;
+        }
+        require!(info.is_writable, MonitoringError::RollupNotWritable);
+
+        let tier = metric
+            .params
+            .rollups
+            .iter()
+            .find(|tier| tier.interval == rollup.interval)
+            .ok_or(MonitoringError::RollupTierMismatch)?;
+        rollup.fold(timestamp, value, tier.retention)?;
+        rollup.exit(&crate::ID)?;
+        applied += 1;
+    }
+
+    // Rollup PDAs are unique per interval, so this also rules out duplicates
+    require!(
+        applied == metric.params.rollups.len(),
+        MonitoringError::RollupTierMismatch
+    );
+    Ok(())
+}
@@ -0,0 +1,87 @@
+// src/instructions/sample_metric.rs
+use anchor_lang::prelude::*;
+use crate::state::{MonitoringConfig, MonitoringState, MetricAccount, MetricHistory, MetricSource};
+use crate::errors::MonitoringError;
+use super::validate_update;
+
+#[derive(Accounts)]
+pub struct SampleMetric<'info> {
+    #[account(
+        seeds = [
+            b"monitoring",
+            monitoring_state.authority.as_ref(),
+            &monitoring_state.namespace.to_le_bytes()
+        ],
+        bump = monitoring_state.bump
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        // Never shrinks, so no rent the authority paid goes to the payer
+        realloc = metric_account
+            .space_after_record(monitoring_state.config.max_data_points)
+            .max(metric_account.to_account_info().data_len()),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(mut)]
+    pub metric_history: Option<AccountLoader<'info, MetricHistory>>,
+
+    /// CHECK: only read, and matched against the metric's configured source
+    pub source_account: UncheckedAccount<'info>,
+
+    /// Funds growth of the metric's inline history
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SampleMetric<'info> {
+    /// Returns the metric's source once the supplied accounts are checked
+    /// against it.
+    pub fn validate(&self, now: i64) -> Result<&MetricSource> {
+        require!(
+            self.metric_account.history
+                == self.metric_history.as_ref().map(|history| history.key()),
+            MonitoringError::HistoryAccountMismatch
+        );
+        // New pages are paid for by the metric authority in `record_metric`
+        require!(!self.metric_account.is_paged(), MonitoringError::MetricPageMismatch);
+        let source = self
+            .metric_account
+            .params
+            .source
+            .as_ref()
+            .ok_or(MonitoringError::MetricNotSourced)?;
+        require_keys_eq!(
+            source.account,
+            self.source_account.key(),
+            MonitoringError::SourceAccountMismatch
+        );
+        validate_sample(&self.monitoring_state.config, &self.metric_account, source, now)?;
+        Ok(source)
+    }
+}
+
+/// Checks a sample taken at `now` against the monitoring state's rate limit
+/// and the source's sample interval. The interval applies even without
+/// rate limiting, since sampling is permissionless.
+pub fn validate_sample(
+    config: &MonitoringConfig,
+    metric: &MetricAccount,
+    source: &MetricSource,
+    now: i64,
+) -> Result<()> {
+    if metric.last_value().is_some() {
+        require!(
+            now.saturating_sub(metric.last_updated) >= i64::from(source.sample_interval),
+            MonitoringError::UpdateTooFrequent
+        );
+    }
+    validate_update(config, metric, now, now)
+}
@@ -0,0 +1,98 @@
+// src/instructions/sample_token_metric.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{MonitoringState, MetricAccount, MetricHistory, SourceField};
+use crate::errors::MonitoringError;
+use super::validate_sample;
+
+#[derive(Accounts)]
+pub struct SampleTokenMetric<'info> {
+    #[account(
+        seeds = [
+            b"monitoring",
+            monitoring_state.authority.as_ref(),
+            &monitoring_state.namespace.to_le_bytes()
+        ],
+        bump = monitoring_state.bump
+    )]
+    pub monitoring_state: Account<'info, MonitoringState>,
+
+    #[account(
+        mut,
+        has_one = monitoring_state,
+        // Never shrinks, so no rent the authority paid goes to the payer
+        realloc = metric_account
+            .space_after_record(monitoring_state.config.max_data_points)
+            .max(metric_account.to_account_info().data_len()),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub metric_account: Account<'info, MetricAccount>,
+
+    #[account(mut)]
+    pub metric_history: Option<AccountLoader<'info, MetricHistory>>,
+
+    /// Mint whose supply is sampled, or the mint of the sampled token account
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account whose balance is sampled, for `TokenBalance` sources
+    pub token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Funds growth of the metric's inline history
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SampleTokenMetric<'info> {
+    /// Checks the supplied accounts against the metric's token source and
+    /// returns the raw amount to record.
+    pub fn validate(&self, now: i64) -> Result<u64> {
+        require!(
+            self.metric_account.history
+                == self.metric_history.as_ref().map(|history| history.key()),
+            MonitoringError::HistoryAccountMismatch
+        );
+        // New pages are paid for by the metric authority in `record_metric`
+        require!(!self.metric_account.is_paged(), MonitoringError::MetricPageMismatch);
+        let source = self
+            .metric_account
+            .params
+            .source
+            .as_ref()
+            .ok_or(MonitoringError::MetricNotSourced)?;
+
+        let amount = match source.field {
+            SourceField::TokenBalance => {
+                let token_account = self
+                    .token_account
+                    .as_ref()
+                    .ok_or(MonitoringError::SourceAccountMismatch)?;
+                require_keys_eq!(
+                    source.account,
+                    token_account.key(),
+                    MonitoringError::SourceAccountMismatch
+                );
+                require_keys_eq!(
+                    token_account.mint,
+                    self.mint.key(),
+                    MonitoringError::SourceAccountMismatch
+                );
+                token_account.amount
+            }
+            SourceField::MintSupply => {
+                require_keys_eq!(
+                    source.account,
+                    self.mint.key(),
+                    MonitoringError::SourceAccountMismatch
+                );
+                self.mint.supply
+            }
+            _ => return err!(MonitoringError::MetricNotSourced),
+        };
+
+        validate_sample(&self.monitoring_state.config, &self.metric_account, source, now)?;
+        Ok(amount)
+    }
+}
@@ -5,9 +5,12 @@ pub mod state;
 pub mod errors;
 pub mod instructions;
 pub mod constants;
+pub mod events;
 
 use instructions::*;
 use state::*;
+use events::*;
+use errors::MonitoringError;
 
 declare_id!("8wq6TVhdTeWiUWXib3vxJguDE9Nm3dmV99YxFCWL3WEe");
 
@@ -17,13 +20,46 @@ pub mod chain_watch {
 
     pub fn initialize_monitoring(
         ctx: Context<InitializeMonitoring>,
+        namespace: u64,
         config: MonitoringConfig,
     ) -> Result<()> {
         let monitoring_state = &mut ctx.accounts.monitoring_state;
         monitoring_state.authority = ctx.accounts.authority.key();
+        monitoring_state.namespace = namespace;
+        monitoring_state.bump = ctx.bumps.monitoring_state;
         monitoring_state.config = config;
         monitoring_state.metrics = Vec::new();
         monitoring_state.alerts = Vec::new();
+        monitoring_state.maintenance_windows = Vec::new();
+        monitoring_state.next_window_id = 0;
+        monitoring_state.composite_alerts = Vec::new();
+        monitoring_state.next_composite_id = 0;
+        monitoring_state.validate_config()?;
+
+        emit!(MonitoringInitialized {
+            monitoring_state: monitoring_state.key(),
+            authority: monitoring_state.authority,
+            namespace,
+            max_metrics: monitoring_state.config.max_metrics,
+            max_alerts: monitoring_state.config.max_alerts,
+            max_data_points: monitoring_state.config.max_data_points,
+        });
+        Ok(())
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, config: MonitoringConfig) -> Result<()> {
+        let monitoring_state = &mut ctx.accounts.monitoring_state;
+        monitoring_state.config = config;
+        monitoring_state.validate_config()?;
+
+        emit!(MonitoringConfigUpdated {
+            monitoring_state: monitoring_state.key(),
+            max_metrics: monitoring_state.config.max_metrics,
+            max_alerts: monitoring_state.config.max_alerts,
+            max_data_points: monitoring_state.config.max_data_points,
+            min_update_interval: monitoring_state.config.min_update_interval,
+            rate_limiting_enabled: monitoring_state.config.rate_limiting_enabled,
+        });
         Ok(())
     }
 
@@ -32,17 +68,40 @@ pub mod chain_watch {
         name: String,
         params: MetricParams,
     ) -> Result<()> {
-        ctx.accounts.validate(&name)?;
+        ctx.accounts.validate(&name, &params)?;
         
         let metric_account = &mut ctx.accounts.metric_account;
+        metric_account.monitoring_state = ctx.accounts.monitoring_state.key();
         metric_account.authority = ctx.accounts.authority.key();
         metric_account.name = name;
+        metric_account.kind_state = MetricKindState::new(&params.kind);
+        metric_account.aggregates = MetricAggregates::default();
         metric_account.params = params;
         metric_account.enabled = true;
         metric_account.last_updated = Clock::get()?.unix_timestamp;
         metric_account.data_points = Vec::new();
+        metric_account.history = None;
+        metric_account.current_page = 0;
+        metric_account.current_page_len = 0;
+        metric_account.oldest_page = 0;
+        metric_account.alert_count = 0;
+        metric_account.next_alert_id = 0;
+        metric_account.source_decimals = None;
+        metric_account.composite_count = 0;
+        metric_account.rollup_count = 0;
+        metric_account.history_floor = 0;
+        metric_account.late_recorded_at = 0;
         
         ctx.accounts.monitoring_state.metrics.push(metric_account.key());
+
+        emit!(MetricAdded {
+            monitoring_state: ctx.accounts.monitoring_state.key(),
+            metric: metric_account.key(),
+            authority: metric_account.authority,
+            name: metric_account.name.clone(),
+            scale: metric_account.params.scale,
+            unit: metric_account.params.unit.clone(),
+        });
         Ok(())
     }
 
@@ -50,14 +109,970 @@ pub mod chain_watch {
         ctx: Context<ConfigureAlert>,
         params: AlertConfigParams,
     ) -> Result<()> {
+        ctx.accounts.validate(&params)?;
+
+        let metric_account = &mut ctx.accounts.metric_account;
         let alert_config = &mut ctx.accounts.alert_config;
-        alert_config.authority = ctx.accounts.authority.key();
-        alert_config.metric = ctx.accounts.metric_account.key();
-        alert_config.params = params;
-        alert_config.enabled = true;
-        alert_config.last_triggered = 0;
+        alert_config.set_inner(AlertConfig::new(
+            ctx.accounts.monitoring_state.key(),
+            ctx.accounts.authority.key(),
+            metric_account.key(),
+            metric_account.next_alert_id,
+            params,
+        ));
         
         ctx.accounts.monitoring_state.alerts.push(alert_config.key());
+        metric_account.alert_count += 1;
+        metric_account.next_alert_id = metric_account
+            .next_alert_id
+            .checked_add(1)
+            .ok_or(MonitoringError::AlertLimitReached)?;
+
+        emit!(AlertConfigured {
+            alert: alert_config.key(),
+            metric: alert_config.metric,
+            alert_id: alert_config.alert_id,
+            threshold_type: alert_config.params.threshold_type.clone(),
+            threshold_value: alert_config.params.threshold_value,
+            secondary_threshold: alert_config.params.secondary_threshold,
+        });
+        Ok(())
+    }
+
+    /// Updates a metric's parameters. The kind, source, paging and rollup
+    /// tiers shape the metric's state and accounts, so they are fixed at
+    /// creation.
+    pub fn update_metric_params(ctx: Context<UpdateMetric>, params: MetricParams) -> Result<()> {
+        ctx.accounts.validate(&params)?;
+
+        let metric_account = &mut ctx.accounts.metric_account;
+        metric_account.params = params;
+
+        emit!(MetricUpdated {
+            metric: metric_account.key(),
+        });
+        Ok(())
+    }
+
+    pub fn set_metric_enabled(ctx: Context<UpdateMetric>, enabled: bool) -> Result<()> {
+        let metric_account = &mut ctx.accounts.metric_account;
+        metric_account.enabled = enabled;
+
+        emit!(MetricStatusChanged {
+            metric: metric_account.key(),
+            enabled,
+        });
+        Ok(())
+    }
+
+    /// Closes a metric together with its history account, its history
+    /// pages and rollups, every alert bound to it and every composite alert depending
+    /// on it, which must be passed in `remaining_accounts`. The other metrics those composite
+    /// alerts depend on must be passed writable as well.
+    pub fn remove_metric<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveMetric<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.validate()?;
+        let alert_infos = accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Alert)?;
+        let composite_infos =
+            accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Composite)?;
+        let metric_infos = accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Metric)?;
+        let page_infos = accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Page)?;
+        let rollup_infos = accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Rollup)?;
+
+        let removed_alerts = ctx.accounts.close_alerts(&alert_infos)?;
+        ctx.accounts.close_pages(&page_infos)?;
+        ctx.accounts.close_rollups(&rollup_infos)?;
+        let authority = ctx.accounts.authority.to_account_info();
+        close_dependent_composites(
+            &mut ctx.accounts.monitoring_state,
+            &ctx.accounts.metric_account,
+            &composite_infos,
+            &metric_infos,
+            &authority,
+            |_| true,
+        )?;
+
+        let metric = ctx.accounts.metric_account.key();
+        let monitoring_state = &mut ctx.accounts.monitoring_state;
+        monitoring_state.metrics.retain(|key| *key != metric);
+        monitoring_state
+            .alerts
+            .retain(|key| !removed_alerts.contains(key));
+
+        emit!(MetricRemoved {
+            monitoring_state: monitoring_state.key(),
+            metric,
+            removed_alerts,
+        });
+        Ok(())
+    }
+
+    pub fn update_alert(ctx: Context<UpdateAlert>, params: AlertConfigParams) -> Result<()> {
+        ctx.accounts.validate(&params)?;
+
+        let alert_config = &mut ctx.accounts.alert_config;
+        alert_config.params = params;
+        reset_alert(alert_config, Clock::get()?.unix_timestamp);
+
+        emit!(AlertUpdated {
+            alert: alert_config.key(),
+            metric: alert_config.metric,
+            threshold_type: alert_config.params.threshold_type.clone(),
+            threshold_value: alert_config.params.threshold_value,
+            secondary_threshold: alert_config.params.secondary_threshold,
+        });
+        Ok(())
+    }
+
+    pub fn set_alert_enabled(ctx: Context<UpdateAlert>, enabled: bool) -> Result<()> {
+        let alert_config = &mut ctx.accounts.alert_config;
+        alert_config.enabled = enabled;
+        if !enabled {
+            reset_alert(alert_config, Clock::get()?.unix_timestamp);
+        }
+
+        emit!(AlertStatusChanged {
+            alert: alert_config.key(),
+            enabled,
+        });
+        Ok(())
+    }
+
+    pub fn acknowledge_alert(ctx: Context<UpdateAlert>) -> Result<()> {
+        ctx.accounts.validate_acknowledge()?;
+
+        let alert_config = &mut ctx.accounts.alert_config;
+        alert_config.acknowledged_by = Some(ctx.accounts.authority.key());
+        alert_config.acknowledged_at = Clock::get()?.unix_timestamp;
+
+        emit!(AlertAcknowledged {
+            alert: alert_config.key(),
+            acknowledged_by: ctx.accounts.authority.key(),
+            acknowledged_at: alert_config.acknowledged_at,
+        });
+        Ok(())
+    }
+
+    /// Prevents the alert from starting to fire until `until`. Passing a
+    /// time in the past clears an existing snooze.
+    pub fn snooze_alert(ctx: Context<UpdateAlert>, until: i64) -> Result<()> {
+        let alert_config = &mut ctx.accounts.alert_config;
+        alert_config.snoozed_until = until;
+
+        emit!(AlertSnoozed {
+            alert: alert_config.key(),
+            snoozed_until: until,
+        });
+        Ok(())
+    }
+
+    pub fn add_maintenance_window(
+        ctx: Context<AddMaintenanceWindow>,
+        start: i64,
+        end: i64,
+        metric: Option<Pubkey>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.monitoring_state.prune_maintenance_windows(now);
+        ctx.accounts.validate(start, end, now)?;
+
+        let monitoring_state = &mut ctx.accounts.monitoring_state;
+        let id = monitoring_state.next_window_id;
+        monitoring_state.maintenance_windows.push(MaintenanceWindow {
+            id,
+            start,
+            end,
+            metric,
+        });
+        monitoring_state.next_window_id = id.wrapping_add(1);
+
+        emit!(MaintenanceWindowAdded {
+            monitoring_state: monitoring_state.key(),
+            window_id: id,
+            start,
+            end,
+            metric,
+        });
+        Ok(())
+    }
+
+    pub fn remove_maintenance_window(
+        ctx: Context<ManageMaintenanceWindows>,
+        window_id: u32,
+    ) -> Result<()> {
+        let monitoring_state = &mut ctx.accounts.monitoring_state;
+        let index = monitoring_state
+            .maintenance_windows
+            .iter()
+            .position(|window| window.id == window_id)
+            .ok_or(MonitoringError::MaintenanceWindowNotFound)?;
+        monitoring_state.maintenance_windows.remove(index);
+
+        emit!(MaintenanceWindowRemoved {
+            monitoring_state: monitoring_state.key(),
+            window_id,
+        });
+        Ok(())
+    }
+
+    /// Closes an alert together with the composite alerts referencing it.
+    /// Every composite alert depending on the alert's metric must be passed
+    /// in `remaining_accounts`, along with the other metrics the closed ones
+    /// depend on, writable.
+    pub fn remove_alert<'info>(ctx: Context<'_, '_, 'info, 'info, RemoveAlert<'info>>) -> Result<()> {
+        let alert = ctx.accounts.alert_config.key();
+        ctx.accounts.monitoring_state.alerts.retain(|key| *key != alert);
+
+        let composite_infos =
+            accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Composite)?;
+        let metric_infos = accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Metric)?;
+        let authority = ctx.accounts.authority.to_account_info();
+        let condition = CompositeCondition::Alert { alert };
+        let closed = close_dependent_composites(
+            &mut ctx.accounts.monitoring_state,
+            &ctx.accounts.metric_account,
+            &composite_infos,
+            &metric_infos,
+            &authority,
+            |composite| composite.conditions.contains(&condition),
+        )?;
+
+        let metric_account = &mut ctx.accounts.metric_account;
+        metric_account.alert_count = metric_account.alert_count.saturating_sub(1);
+        let closed = u16::try_from(closed.len()).map_err(|_| MonitoringError::ArithmeticOverflow)?;
+        metric_account.composite_count = metric_account.composite_count.saturating_sub(closed);
+
+        emit!(AlertRemoved {
+            monitoring_state: ctx.accounts.monitoring_state.key(),
+            alert,
+            metric: metric_account.key(),
+        });
+        Ok(())
+    }
+
+    /// Creates an alert combining existing alerts and metric conditions.
+    /// Every referenced account must be passed in `remaining_accounts`,
+    /// along with the metrics of referenced alerts. The metrics are linked
+    /// to the composite alert, so they must be writable.
+    pub fn create_composite_alert<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateCompositeAlert<'info>>,
+        operator: CompositeOperator,
+        conditions: Vec<CompositeCondition>,
+    ) -> Result<()> {
+        let metrics = ctx
+            .accounts
+            .validate(operator, &conditions, ctx.remaining_accounts)?;
+        let metric_infos = accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Metric)?;
+        update_composite_counts(&metrics, &metric_infos, true)?;
+
+        let monitoring_state = &mut ctx.accounts.monitoring_state;
+        let composite_alert = &mut ctx.accounts.composite_alert;
+        composite_alert.monitoring_state = monitoring_state.key();
+        composite_alert.authority = ctx.accounts.authority.key();
+        composite_alert.composite_id = monitoring_state.next_composite_id;
+        composite_alert.operator = operator;
+        composite_alert.conditions = conditions;
+        composite_alert.enabled = true;
+        composite_alert.state = AlertState::Ok;
+        composite_alert.firing_since = 0;
+        composite_alert.resolved_at = 0;
+        composite_alert.metrics = metrics;
+        composite_alert.acknowledged_by = None;
+        composite_alert.acknowledged_at = 0;
+        composite_alert.snoozed_until = 0;
+
+        monitoring_state.composite_alerts.push(composite_alert.key());
+        monitoring_state.next_composite_id = monitoring_state
+            .next_composite_id
+            .checked_add(1)
+            .ok_or(MonitoringError::AlertLimitReached)?;
+
+        emit!(CompositeAlertCreated {
+            monitoring_state: monitoring_state.key(),
+            composite_alert: composite_alert.key(),
+            operator,
+            conditions: composite_alert.conditions.clone(),
+        });
+        Ok(())
+    }
+
+    /// Permissionlessly re-evaluates a composite alert from the alerts and
+    /// metrics passed in `remaining_accounts`.
+    pub fn evaluate_composite_alert<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EvaluateCompositeAlert<'info>>,
+    ) -> Result<bool> {
+        let mut snapshot = ConditionSnapshot::default();
+        for info in ctx.remaining_accounts {
+            match MonitoringAccountKind::of(info)? {
+                MonitoringAccountKind::Alert => {
+                    let alert = Account::<AlertConfig>::try_from(info)?;
+                    snapshot.add_alert(alert.key(), alert.state);
+                }
+                MonitoringAccountKind::Metric => {
+                    let metric = Account::<MetricAccount>::try_from(info)?;
+                    snapshot.add_metric(metric.key(), metric.last_value());
+                }
+                MonitoringAccountKind::Composite
+                | MonitoringAccountKind::Rollup
+                | MonitoringAccountKind::Page
+                | MonitoringAccountKind::History => {
+                    return err!(MonitoringError::UnexpectedAccount);
+                }
+            }
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let composite_alert = &mut ctx.accounts.composite_alert;
+        let fired = evaluate_composite(composite_alert, &snapshot, &ctx.accounts.monitoring_state, now)?;
+        Ok(fired)
+    }
+
+    pub fn acknowledge_composite_alert(ctx: Context<UpdateCompositeAlert>) -> Result<()> {
+        ctx.accounts.validate_acknowledge()?;
+
+        let composite_alert = &mut ctx.accounts.composite_alert;
+        composite_alert.acknowledged_by = Some(ctx.accounts.authority.key());
+        composite_alert.acknowledged_at = Clock::get()?.unix_timestamp;
+
+        emit!(CompositeAlertAcknowledged {
+            composite_alert: composite_alert.key(),
+            acknowledged_by: ctx.accounts.authority.key(),
+            acknowledged_at: composite_alert.acknowledged_at,
+        });
+        Ok(())
+    }
+
+    /// Prevents the composite alert from starting to fire until `until`.
+    /// Passing a time in the past clears an existing snooze.
+    pub fn snooze_composite_alert(ctx: Context<UpdateCompositeAlert>, until: i64) -> Result<()> {
+        let composite_alert = &mut ctx.accounts.composite_alert;
+        composite_alert.snoozed_until = until;
+
+        emit!(CompositeAlertSnoozed {
+            composite_alert: composite_alert.key(),
+            snoozed_until: until,
+        });
+        Ok(())
+    }
+
+    /// Closes a composite alert, unlinking it from the metrics it depends
+    /// on, which must be passed writable in `remaining_accounts`.
+    pub fn remove_composite_alert<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveCompositeAlert<'info>>,
+    ) -> Result<()> {
+        let metric_infos = accounts_of_kind(ctx.remaining_accounts, MonitoringAccountKind::Metric)?;
+        update_composite_counts(&ctx.accounts.composite_alert.metrics, &metric_infos, false)?;
+
+        let composite_alert = ctx.accounts.composite_alert.key();
+        ctx.accounts
+            .monitoring_state
+            .composite_alerts
+            .retain(|key| *key != composite_alert);
+
+        emit!(CompositeAlertRemoved {
+            monitoring_state: ctx.accounts.monitoring_state.key(),
+            composite_alert,
+        });
+        Ok(())
+    }
+
+    /// Permissionlessly checks whether a metric has gone without updates for
+    /// longer than its `Stale` alert allows, firing or resolving the alert.
+    pub fn check_staleness(ctx: Context<CheckStaleness>) -> Result<()> {
+        ctx.accounts.validate()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let metric = ctx.accounts.metric_account.key();
+        let last_updated = ctx.accounts.metric_account.last_updated;
+        let in_maintenance = ctx.accounts.monitoring_state.in_maintenance(&metric, now);
+        let staleness = AlertConfig::staleness(last_updated, now);
+
+        let alert = &mut ctx.accounts.alert_config;
+        match alert.evaluate_staleness(last_updated, now, in_maintenance) {
+            AlertTransition::Triggered => {
+                msg!("Metric {} has not been updated for {}s", metric, staleness);
+                emit!(AlertTriggered {
+                    alert: alert.key(),
+                    metric,
+                    value: staleness,
+                    scale: 0,
+                    unit: String::from("s"),
+                    consecutive_violations: alert.consecutive_violations,
+                    triggered_at: now,
+                });
+            }
+            AlertTransition::Resolved => {
+                emit!(AlertResolved {
+                    alert: alert.key(),
+                    metric,
+                    value: staleness,
+                    scale: 0,
+                    unit: String::from("s"),
+                    firing_since: alert.firing_since,
+                    resolved_at: now,
+                });
+            }
+            AlertTransition::Unchanged => {}
+        }
+        Ok(())
+    }
+
+    /// Records a data point and evaluates the alerts passed in
+    /// `remaining_accounts`, returning the keys of the alerts that fired.
+    /// Every alert bound to the metric and every composite alert depending
+    /// on it must be passed.
+    ///
+    /// Points within the metric's late-arrival window are inserted in order
+    /// and folded into its rollups. A late point that violates an alert
+    /// counts towards firing it, but only the latest point can resolve one.
+    pub fn record_metric<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecordMetric<'info>>,
+        timestamp: i64,
+        value: i64,
+    ) -> Result<Vec<Pubkey>> {
+        ctx.accounts.validate(timestamp)?;
+
+        let metric_account = &mut ctx.accounts.metric_account;
+        let previous = metric_account.last_value();
+        let outcome = record_point(
+            metric_account,
+            ctx.accounts.metric_history.as_ref(),
+            ctx.accounts.metric_page.as_mut(),
+            ctx.accounts.monitoring_state.config.max_data_points,
+            timestamp,
+            value,
+        )?;
+        if outcome == RecordOutcome::Duplicate {
+            return Ok(Vec::new());
+        }
+        if matches!(outcome, RecordOutcome::Inserted { .. }) {
+            metric_account.late_recorded_at = Clock::get()?.unix_timestamp;
+        }
+
+        emit!(MetricRecorded {
+            metric: metric_account.key(),
+            timestamp,
+            value,
+            scale: metric_account.params.scale,
+            unit: metric_account.params.unit.clone(),
+        });
+
+        apply_rollups(metric_account, ctx.remaining_accounts, timestamp, value)?;
+        evaluate_alerts(
+            &ctx.accounts.monitoring_state,
+            metric_account,
+            ctx.remaining_accounts,
+            value,
+            previous,
+            outcome,
+        )
+    }
+
+    /// Permissionlessly reads a sourced metric's value from its source
+    /// account and records it at the current cluster time, evaluating the
+    /// alerts passed in `remaining_accounts` as `record_metric` does.
+    pub fn sample_metric<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SampleMetric<'info>>,
+    ) -> Result<Vec<Pubkey>> {
+        let now = Clock::get()?.unix_timestamp;
+        let value = ctx
+            .accounts
+            .validate(now)?
+            .read(&ctx.accounts.source_account)?;
+
+        let metric_account = &mut ctx.accounts.metric_account;
+        let previous = metric_account.last_value();
+        let outcome = record_point(
+            metric_account,
+            ctx.accounts.metric_history.as_ref(),
+            None,
+            ctx.accounts.monitoring_state.config.max_data_points,
+            now,
+            value,
+        )?;
+        // Sampled at the cluster time, so never late
+        if outcome == RecordOutcome::Duplicate {
+            return Ok(Vec::new());
+        }
+
+        emit!(MetricSampled {
+            metric: metric_account.key(),
+            source: ctx.accounts.source_account.key(),
+            timestamp: now,
+            value,
+            scale: metric_account.params.scale,
+            unit: metric_account.params.unit.clone(),
+        });
+
+        apply_rollups(metric_account, ctx.remaining_accounts, now, value)?;
+        evaluate_alerts(
+            &ctx.accounts.monitoring_state,
+            metric_account,
+            ctx.remaining_accounts,
+            value,
+            previous,
+            outcome,
+        )
+    }
+
+    /// Permissionlessly samples an SPL Token or Token-2022 balance or mint
+    /// supply. The raw amount is recorded and the mint decimals are kept on
+    /// the metric so clients can scale it.
+    pub fn sample_token_metric<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SampleTokenMetric<'info>>,
+    ) -> Result<Vec<Pubkey>> {
+        let now = Clock::get()?.unix_timestamp;
+        let amount = ctx.accounts.validate(now)?;
+        let value = i64::try_from(amount).map_err(|_| MonitoringError::TokenAmountOverflow)?;
+        let decimals = ctx.accounts.mint.decimals;
+
+        let metric_account = &mut ctx.accounts.metric_account;
+        let previous = metric_account.last_value();
+        let outcome = record_point(
+            metric_account,
+            ctx.accounts.metric_history.as_ref(),
+            None,
+            ctx.accounts.monitoring_state.config.max_data_points,
+            now,
+            value,
+        )?;
+        // Sampled at the cluster time, so never late
+        if outcome == RecordOutcome::Duplicate {
+            return Ok(Vec::new());
+        }
+        metric_account.source_decimals = Some(decimals);
+
+        emit!(TokenMetricSampled {
+            metric: metric_account.key(),
+            mint: ctx.accounts.mint.key(),
+            token_account: ctx.accounts.token_account.as_ref().map(|account| account.key()),
+            timestamp: now,
+            amount,
+            decimals,
+        });
+
+        apply_rollups(metric_account, ctx.remaining_accounts, now, value)?;
+        evaluate_alerts(
+            &ctx.accounts.monitoring_state,
+            metric_account,
+            ctx.remaining_accounts,
+            value,
+            previous,
+            outcome,
+        )
+    }
+
+    /// Starts watching an upgradeable program, recording its current
+    /// deployment slot, upgrade authority and executable hash.
+    pub fn watch_program(ctx: Context<WatchProgram>) -> Result<()> {
+        let code_hash = executable_hash(&ctx.accounts.program_data.to_account_info())?;
+        let program_data = &ctx.accounts.program_data;
+
+        let program_watch = &mut ctx.accounts.program_watch;
+        program_watch.monitoring_state = ctx.accounts.monitoring_state.key();
+        program_watch.authority = ctx.accounts.authority.key();
+        program_watch.program = ctx.accounts.program.key();
+        program_watch.program_data = program_data.key();
+        program_watch.last_deployed_slot = program_data.slot;
+        program_watch.upgrade_authority = program_data.upgrade_authority_address;
+        program_watch.code_hash = code_hash;
+        program_watch.last_checked = Clock::get()?.unix_timestamp;
+        program_watch.upgrade_count = 0;
+
+        emit!(ProgramWatchAdded {
+            monitoring_state: program_watch.monitoring_state,
+            program_watch: program_watch.key(),
+            program: program_watch.program,
+            deployed_slot: program_watch.last_deployed_slot,
+            upgrade_authority: program_watch.upgrade_authority,
+            code_hash,
+        });
+        Ok(())
+    }
+
+    /// Permissionlessly compares a watched program's deployment against
+    /// the stored one, emitting an event when it was redeployed or its
+    /// upgrade authority changed. Returns whether anything changed.
+    pub fn check_program_watch(ctx: Context<CheckProgramWatch>) -> Result<bool> {
+        let code_hash = executable_hash(&ctx.accounts.program_data.to_account_info())?;
+        let program_data = &ctx.accounts.program_data;
+        let now = Clock::get()?.unix_timestamp;
+
+        let program_watch = &mut ctx.accounts.program_watch;
+        let previous_slot = program_watch.last_deployed_slot;
+        let previous_hash = program_watch.code_hash;
+        let previous_authority = program_watch.upgrade_authority;
+        let change = program_watch.observe(
+            program_data.slot,
+            program_data.upgrade_authority_address,
+            code_hash,
+            now,
+        );
+
+        if change.redeployed {
+            msg!("Program {} was redeployed", program_watch.program);
+            emit!(ProgramUpgraded {
+                program_watch: program_watch.key(),
+                program: program_watch.program,
+                previous_slot,
+                deployed_slot: program_watch.last_deployed_slot,
+                previous_hash,
+                code_hash,
+                detected_at: now,
+            });
+        }
+        if change.authority_changed {
+            msg!("Upgrade authority of program {} changed", program_watch.program);
+            emit!(ProgramUpgradeAuthorityChanged {
+                program_watch: program_watch.key(),
+                program: program_watch.program,
+                previous_authority,
+                upgrade_authority: program_watch.upgrade_authority,
+                detected_at: now,
+            });
+        }
+        Ok(change.redeployed || change.authority_changed)
+    }
+
+    pub fn remove_program_watch(ctx: Context<RemoveProgramWatch>) -> Result<()> {
+        emit!(ProgramWatchRemoved {
+            monitoring_state: ctx.accounts.monitoring_state.key(),
+            program_watch: ctx.accounts.program_watch.key(),
+        });
+        Ok(())
+    }
+
+    /// Starts watching an account for changes to its data (optionally only
+    /// `range`), owner or lamports, together with the `AccountChange` alert
+    /// fired when it changes.
+    pub fn watch_account(
+        ctx: Context<WatchAccount>,
+        range: Option<DataRange>,
+        params: AlertConfigParams,
+    ) -> Result<()> {
+        ctx.accounts.validate(range, &params)?;
+
+        let target = &ctx.accounts.target;
+        let data_hash = watched_data_hash(target, range)?;
+        let alert_config = &mut ctx.accounts.alert_config;
+        let account_watch = &mut ctx.accounts.account_watch;
+
+        account_watch.monitoring_state = ctx.accounts.monitoring_state.key();
+        account_watch.authority = ctx.accounts.authority.key();
+        account_watch.target = target.key();
+        account_watch.range = range;
+        account_watch.alert = alert_config.key();
+        account_watch.data_hash = data_hash;
+        account_watch.owner = *target.owner;
+        account_watch.lamports = target.lamports();
+        account_watch.last_checked = Clock::get()?.unix_timestamp;
+        account_watch.change_count = 0;
+
+        alert_config.set_inner(AlertConfig::new(
+            account_watch.monitoring_state,
+            account_watch.authority,
+            account_watch.key(),
+            0,
+            params,
+        ));
+
+        ctx.accounts.monitoring_state.alerts.push(alert_config.key());
+
+        emit!(AccountWatchAdded {
+            monitoring_state: account_watch.monitoring_state,
+            account_watch: account_watch.key(),
+            alert: alert_config.key(),
+            target: account_watch.target,
+            data_hash,
+            owner: account_watch.owner,
+            lamports: account_watch.lamports,
+        });
+        Ok(())
+    }
+
+    /// Permissionlessly compares a watched account against the stored
+    /// baseline. Any difference is emitted and fires the watch's alert,
+    /// which keeps firing until the authority accepts the new state with
+    /// `reset_account_watch`.
+    pub fn check_account_watch(ctx: Context<CheckAccountWatch>) -> Result<bool> {
+        let now = Clock::get()?.unix_timestamp;
+        let target = &ctx.accounts.target;
+        let account_watch = &mut ctx.accounts.account_watch;
+        let data_hash = watched_data_hash(target, account_watch.range)?;
+
+        let previous_hash = account_watch.data_hash;
+        let previous_owner = account_watch.owner;
+        let previous_lamports = account_watch.lamports;
+        let diff = account_watch.observe(data_hash, *target.owner, target.lamports(), now);
+
+        if diff.any() {
+            msg!("Watched account {} changed", account_watch.target);
+            emit!(AccountChanged {
+                account_watch: account_watch.key(),
+                target: account_watch.target,
+                data_changed: diff.data_changed,
+                previous_hash,
+                data_hash,
+                previous_owner,
+                owner: *target.owner,
+                previous_lamports,
+                lamports: target.lamports(),
+                detected_at: now,
+            });
+        }
+
+        let watch = account_watch.key();
+        let change_count = i64::from(account_watch.change_count);
+        let in_maintenance = ctx.accounts.monitoring_state.in_maintenance(&watch, now);
+        let alert = &mut ctx.accounts.alert_config;
+        match alert.evaluate_account_change(diff.any(), now, in_maintenance) {
+            AlertTransition::Triggered => {
+                emit!(AlertTriggered {
+                    alert: alert.key(),
+                    metric: watch,
+                    value: change_count,
+                    scale: 0,
+                    unit: String::new(),
+                    consecutive_violations: alert.consecutive_violations,
+                    triggered_at: now,
+                });
+            }
+            AlertTransition::Resolved => {
+                emit!(AlertResolved {
+                    alert: alert.key(),
+                    metric: watch,
+                    value: change_count,
+                    scale: 0,
+                    unit: String::new(),
+                    firing_since: alert.firing_since,
+                    resolved_at: now,
+                });
+            }
+            AlertTransition::Unchanged => {}
+        }
+        Ok(diff.any())
+    }
+
+    /// Accepts the watched account's current state as the baseline of the
+    /// watch, resolving its alert if it is firing.
+    pub fn reset_account_watch(ctx: Context<ResetAccountWatch>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let target = &ctx.accounts.target;
+        let account_watch = &mut ctx.accounts.account_watch;
+        let data_hash = watched_data_hash(target, account_watch.range)?;
+        account_watch.set_baseline(data_hash, *target.owner, target.lamports(), now);
+        reset_alert(&mut ctx.accounts.alert_config, now);
+
+        emit!(AccountWatchBaselineReset {
+            account_watch: account_watch.key(),
+            target: account_watch.target,
+            data_hash,
+            owner: account_watch.owner,
+            lamports: account_watch.lamports,
+            reset_at: now,
+        });
+        Ok(())
+    }
+
+    /// Closes an account watch together with its alert.
+    pub fn remove_account_watch(ctx: Context<RemoveAccountWatch>) -> Result<()> {
+        let alert = ctx.accounts.alert_config.key();
+        ctx.accounts.monitoring_state.alerts.retain(|key| *key != alert);
+
+        emit!(AccountWatchRemoved {
+            monitoring_state: ctx.accounts.monitoring_state.key(),
+            account_watch: ctx.accounts.account_watch.key(),
+        });
+        Ok(())
+    }
+
+    /// Records many data points at once. Metric accounts are passed in
+    /// `remaining_accounts` and referenced by index; entries rejected by
+    /// validation are reported back instead of aborting the batch and leave
+    /// their metric untouched. Points are recorded to a metric's attached
+    /// zero-copy history and folded into its rollups, and alerts are
+    /// evaluated for every recorded point as in `record_metric`, so the
+    /// history, every rollup, alert and composite alert of the batched
+    /// metrics must be passed in `remaining_accounts` too. Sourced and paged
+    /// metrics can't be batched.
+    pub fn record_metrics_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecordMetricsBatch<'info>>,
+        entries: Vec<BatchRecordEntry>,
+    ) -> Result<Vec<BatchEntryFailure>> {
+        let mut metrics = ctx.accounts.load_metrics(ctx.remaining_accounts)?;
+        let monitoring_state = &ctx.accounts.monitoring_state;
+        let config = &monitoring_state.config;
+        let authority = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let now = Clock::get()?.unix_timestamp;
+        let mut failures = Vec::new();
+
+        for (entry_index, entry) in entries.iter().enumerate() {
+            let metric = metrics
+                .get_mut(usize::from(entry.metric_index))
+                .ok_or(MonitoringError::InvalidBatchEntry)?;
+            let previous = metric.last_value();
+            let history = batch_history(ctx.remaining_accounts, metric)?;
+
+            let result = validate_batch_entry(config, metric, entry.timestamp, now).and_then(|()| {
+                match &history {
+                    Some(history) => {
+                        let mut history = history.load_mut()?;
+                        metric.record_to_history(&mut history, entry.timestamp, entry.value)
+                    }
+                    None => metric.record(entry.timestamp, entry.value, config.max_data_points),
+                }
+            });
+
+            match result {
+                Ok(RecordOutcome::Duplicate) => emit!(MetricPointDeduplicated {
+                    metric: metric.key(),
+                    timestamp: entry.timestamp,
+                    value: entry.value,
+                }),
+                Ok(outcome) => {
+                    if matches!(outcome, RecordOutcome::Inserted { .. }) {
+                        metric.late_recorded_at = now;
+                    }
+                    emit!(MetricRecorded {
+                        metric: metric.key(),
+                        timestamp: entry.timestamp,
+                        value: entry.value,
+                        scale: metric.params.scale,
+                        unit: metric.params.unit.clone(),
+                    });
+
+                    // Persisted right away, so composite alerts evaluated for
+                    // later entries read the new value
+                    grow_account(
+                        &metric.to_account_info(),
+                        MetricAccount::space(metric.data_points.len()),
+                        &authority,
+                        &system_program,
+                    )?;
+                    metric.exit(&crate::ID)?;
+
+                    apply_rollups(metric, ctx.remaining_accounts, entry.timestamp, entry.value)?;
+                    evaluate_alerts(
+                        monitoring_state,
+                        metric,
+                        ctx.remaining_accounts,
+                        entry.value,
+                        previous,
+                        outcome,
+                    )?;
+                }
+                Err(err) => {
+                    let error_code = u64::from(ProgramError::from(err));
+                    emit!(MetricRecordRejected {
+                        metric: metric.key(),
+                        timestamp: entry.timestamp,
+                        value: entry.value,
+                        error_code,
+                    });
+                    failures.push(BatchEntryFailure {
+                        entry_index: u16::try_from(entry_index)
+                            .map_err(|_| MonitoringError::InvalidBatchEntry)?,
+                        error_code,
+                    });
+                }
+            }
+        }
+        Ok(failures)
+    }
+
+    /// Closes the oldest history page of a paged metric once more pages
+    /// are open than it retains, returning the rent to the authority.
+    pub fn close_metric_page(ctx: Context<CloseMetricPage>) -> Result<()> {
+        ctx.accounts.validate()?;
+
+        let metric_account = &mut ctx.accounts.metric_account;
+        let page_index = metric_account.close_oldest_page(&ctx.accounts.metric_page);
+
+        emit!(MetricPageClosed {
+            metric: metric_account.key(),
+            metric_page: ctx.accounts.metric_page.key(),
+            page_index,
+        });
+        Ok(())
+    }
+
+    /// Creates the account holding one of a metric's rollup tiers. Every
+    /// configured tier must exist before the metric can be recorded.
+    pub fn initialize_rollup(ctx: Context<InitializeRollup>, interval: RollupInterval) -> Result<()> {
+        ctx.accounts.validate(interval)?;
+
+        let metric_rollup = &mut ctx.accounts.metric_rollup;
+        metric_rollup.metric = ctx.accounts.metric_account.key();
+        metric_rollup.interval = interval;
+        metric_rollup.buckets = Vec::new();
+
+        // Bounded by the configured tiers, since each tier has one PDA
+        let metric_account = &mut ctx.accounts.metric_account;
+        metric_account.rollup_count += 1;
+
+        emit!(MetricRollupInitialized {
+            metric: metric_rollup.metric,
+            metric_rollup: metric_rollup.key(),
+            interval,
+        });
+        Ok(())
+    }
+
+    /// Permissionlessly drops a metric's data points older than its TTL,
+    /// from its inline points or attached history, and closes the expired
+    /// history pages passed in `remaining_accounts` oldest first. Freed
+    /// rent is refunded to the metric authority.
+    pub fn prune_metric<'info>(ctx: Context<'_, '_, 'info, 'info, PruneMetric<'info>>) -> Result<()> {
+        ctx.accounts.validate()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut removed = ctx.accounts.close_expired_pages(ctx.remaining_accounts, now)?;
+        let metric_account = &mut ctx.accounts.metric_account;
+        removed += metric_account.prune(now);
+        if let Some(metric_history) = &ctx.accounts.metric_history {
+            let mut history = metric_history.load_mut()?;
+            removed += metric_account.prune_history(&mut history, now);
+        }
+
+        let refunded = shrink_account(
+            &metric_account.to_account_info(),
+            MetricAccount::space(metric_account.data_points.len()),
+            &ctx.accounts.authority.to_account_info(),
+        )?;
+
+        emit!(MetricPruned {
+            metric: metric_account.key(),
+            removed: u32::try_from(removed).map_err(|_| MonitoringError::ArithmeticOverflow)?,
+            refunded,
+        });
+        Ok(())
+    }
+
+    /// Attaches a zero-copy history to a metric, moving its inline data
+    /// points into it and refunding the freed rent to the authority.
+    pub fn initialize_metric_history(ctx: Context<InitializeMetricHistory>) -> Result<()> {
+        ctx.accounts.validate()?;
+
+        let metric_account = &mut ctx.accounts.metric_account;
+        let mut history = ctx.accounts.metric_history.load_init()?;
+        history.metric = metric_account.key();
+        history.head = 0;
+        history.len = 0;
+        metric_account.attach_history(ctx.accounts.metric_history.key(), &mut history);
+        drop(history);
+
+        shrink_account(
+            &metric_account.to_account_info(),
+            MetricAccount::space(0),
+            &ctx.accounts.authority.to_account_info(),
+        )?;
+
+        emit!(MetricHistoryAttached {
+            metric: ctx.accounts.metric_account.key(),
+            history: ctx.accounts.metric_history.key(),
+        });
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,39 +1,119 @@
 use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use crate::errors::MonitoringError;
+use crate::constants::{
+    BASIS_POINTS, COUNTER_RATE_DECIMALS, DEFAULT_EWMA_ALPHA_BPS, MAX_ALERTS, MAX_COMPOSITE_CONDITIONS, MAX_DATA_POINTS,
+    MAX_HISTOGRAM_BUCKETS, MAX_HISTOGRAM_COUNTS, MAX_HISTORY_POINTS, MAX_MAINTENANCE_WINDOWS,
+    MAX_METRICS, MAX_NAME_LENGTH, MAX_OPEN_PAGES, MAX_PAGE_POINTS, PAGE_LATE_SLOTS, MAX_ROLLUP_BUCKETS, MAX_ROLLUP_TIERS,
+    MAX_SCALE, MAX_UNIT_LENGTH, MAX_WEBHOOK_URL_LENGTH, MIN_SAMPLE_INTERVAL,
+};
 
 /// The main state account for the monitoring system
 #[account]
-#[derive(Default)]
+#[derive(Default, InitSpace)]
 pub struct MonitoringState {
     /// Authority that can manage the monitoring configuration
     pub authority: Pubkey,
+    /// Caller-chosen id distinguishing monitoring states of the same authority
+    pub namespace: u64,
+    /// PDA bump seed
+    pub bump: u8,
     /// Global monitoring configuration
     pub config: MonitoringConfig,
     /// List of all metric account pubkeys being tracked
+    #[max_len(MAX_METRICS)]
     pub metrics: Vec<Pubkey>,
     /// List of all alert configuration pubkeys
+    #[max_len(MAX_ALERTS)]
     pub alerts: Vec<Pubkey>,
+    /// Scheduled periods during which alerts may not start firing
+    #[max_len(MAX_MAINTENANCE_WINDOWS)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    /// Id assigned to the next maintenance window
+    pub next_window_id: u32,
+    /// List of all composite alert pubkeys
+    #[max_len(MAX_ALERTS)]
+    pub composite_alerts: Vec<Pubkey>,
+    /// Id assigned to the next composite alert
+    pub next_composite_id: u32,
 }
 
 impl MonitoringState {
+    /// Account size with empty collections. `INIT_SPACE` covers every
+    /// collection at its `max_len` bound.
+    pub const BASE_SPACE: usize = 8 + Self::INIT_SPACE
+        - (MAX_METRICS as usize + 2 * MAX_ALERTS as usize) * 32
+        - MAX_MAINTENANCE_WINDOWS * MaintenanceWindow::INIT_SPACE;
+
+    /// Account size for the current contents of the collections.
+    pub fn space(&self) -> usize {
+        Self::BASE_SPACE
+            + (self.metrics.len() + self.alerts.len() + self.composite_alerts.len()) * 32
+            + self.maintenance_windows.len() * MaintenanceWindow::INIT_SPACE
+    }
+
     pub fn validate_config(&self) -> Result<()> {
         require!(
-            self.config.max_metrics > 0 && self.config.max_metrics <= 100,
+            self.config.max_metrics > 0 && self.config.max_metrics <= MAX_METRICS,
             MonitoringError::InvalidMaxMetrics
         );
         require!(
-            self.config.max_alerts > 0 && self.config.max_alerts <= 50,
+            self.config.max_alerts > 0 && self.config.max_alerts <= MAX_ALERTS,
             MonitoringError::InvalidMaxAlerts
         );
         require!(
-            self.config.max_data_points > 0 && self.config.max_data_points <= 1000,
+            self.config.max_data_points > 0 && self.config.max_data_points <= MAX_DATA_POINTS,
             MonitoringError::InvalidMaxDataPoints
         );
+        require!(
+            self.config.min_update_interval >= 0,
+            MonitoringError::InvalidUpdateInterval
+        );
         Ok(())
     }
+
+    /// Number of alerts and composite alerts counted against `max_alerts`.
+    pub fn alert_count(&self) -> usize {
+        self.alerts.len() + self.composite_alerts.len()
+    }
+
+    /// Drops maintenance windows that have already ended.
+    pub fn prune_maintenance_windows(&mut self, now: i64) {
+        self.maintenance_windows.retain(|window| window.end > now);
+    }
+
+    /// Whether `metric` is covered by an active maintenance window.
+    pub fn in_maintenance(&self, metric: &Pubkey, now: i64) -> bool {
+        self.maintenance_windows
+            .iter()
+            .any(|window| window.covers(metric, now))
+    }
+}
+
+/// A scheduled period during which alerts keep counting violations but do
+/// not transition to firing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct MaintenanceWindow {
+    /// Identifier used to remove the window
+    pub id: u32,
+    /// Start of the window (unix timestamp, inclusive)
+    pub start: i64,
+    /// End of the window (unix timestamp, exclusive)
+    pub end: i64,
+    /// Restricts the window to a single metric when set
+    pub metric: Option<Pubkey>,
+}
+
+impl MaintenanceWindow {
+    pub fn covers(&self, metric: &Pubkey, now: i64) -> bool {
+        self.start <= now
+            && now < self.end
+            && (self.metric.is_none() || self.metric == Some(*metric))
+    }
 }
 
 /// Global configuration parameters for the monitoring system
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
 pub struct MonitoringConfig {
     /// Maximum number of metrics that can be tracked
     pub max_metrics: u16,
@@ -49,11 +129,14 @@ pub struct MonitoringConfig {
 
 /// An individual metric tracking account
 #[account]
-#[derive(Default)]
+#[derive(Default, InitSpace)]
 pub struct MetricAccount {
+    /// Monitoring state this metric belongs to
+    pub monitoring_state: Pubkey,
     /// Authority allowed to update this metric
     pub authority: Pubkey,
     /// Name/identifier of the metric
+    #[max_len(MAX_NAME_LENGTH)]
     pub name: String,
     /// Metric-specific parameters
     pub params: MetricParams,
@@ -62,18 +145,186 @@ pub struct MetricAccount {
     /// Last time the metric was updated (unix timestamp)
     pub last_updated: i64,
     /// Historical data points for this metric
+    #[max_len(MAX_DATA_POINTS)]
     pub data_points: Vec<MetricDataPoint>,
+    /// Zero-copy history account, used instead of `data_points` when attached
+    pub history: Option<Pubkey>,
+    /// Index of the history page currently written to
+    pub current_page: u64,
+    /// Number of points in the current history page
+    pub current_page_len: u32,
+    /// Index of the oldest history page still open
+    pub oldest_page: u64,
+    /// Extremes of each open history page, oldest first
+    #[max_len(MAX_OPEN_PAGES)]
+    pub page_windows: Vec<PageWindow>,
+    /// State derived from recorded values according to `params.kind`
+    pub kind_state: MetricKindState,
+    /// Running summaries of the recorded values
+    pub aggregates: MetricAggregates,
+    /// Number of alerts currently bound to this metric
+    pub alert_count: u16,
+    /// Id assigned to the next alert configured on this metric
+    pub next_alert_id: u16,
+    /// Decimals of the mint behind a token source, set when sampled
+    pub source_decimals: Option<u8>,
+    /// Number of composite alerts depending on this metric
+    pub composite_count: u16,
+    /// Number of rollup tiers whose `MetricRollup` account exists
+    pub rollup_count: u8,
+    /// Timestamp of the newest point evicted, pruned or never stored.
+    /// Duplicates at or before it can't be detected, so late points must
+    /// be newer.
+    pub history_floor: i64,
+    /// Cluster time of the last late point inserted, which late points are
+    /// rate limited against
+    pub late_recorded_at: i64,
 }
 
 impl MetricAccount {
+    /// The most recently recorded value, if any.
+    pub fn last_value(&self) -> Option<i64> {
+        (self.aggregates.count > 0).then_some(self.aggregates.last)
+    }
+
+    /// Whether history is kept in `MetricPage` accounts. An attached
+    /// zero-copy history takes precedence.
+    pub fn is_paged(&self) -> bool {
+        self.params.max_pages > 0 && self.history.is_none()
+    }
+
+    /// Index of the page the next point is written to, rolling over once
+    /// the current page only has its `PAGE_LATE_SLOTS` left, so that late
+    /// points can still be inserted into every open page.
+    pub fn write_page_index(&self) -> u64 {
+        if self.current_page_len as usize >= MAX_PAGE_POINTS - PAGE_LATE_SLOTS {
+            self.current_page + 1
+        } else {
+            self.current_page
+        }
+    }
+
+    /// Number of history pages currently open.
+    pub fn open_pages(&self) -> u64 {
+        self.current_page - self.oldest_page + 1
+    }
+
+    /// Number of history page accounts that exist. The first page is only
+    /// created once a point is recorded to it.
+    pub fn existing_pages(&self) -> u64 {
+        if self.current_page_len > 0 {
+            self.open_pages()
+        } else {
+            0
+        }
+    }
+
+    /// Index of the page a point at `timestamp` is written to. Late points
+    /// go to the newest open page starting at or before them, or to the
+    /// oldest open page if they precede every page.
+    pub fn page_index_for(&self, timestamp: i64) -> u64 {
+        if !self.is_late(timestamp) {
+            return self.write_page_index();
+        }
+        self.page_windows
+            .iter()
+            .rev()
+            .find(|window| window.first_timestamp <= timestamp)
+            .or(self.page_windows.first())
+            .map_or(self.current_page, |window| window.page_index)
+    }
+
+    /// Forgets the oldest open history page, which is being closed, and
+    /// narrows the window to the pages still open. Returns its index.
+    pub fn close_oldest_page(&mut self, page: &MetricPage) -> u64 {
+        let page_index = self.oldest_page;
+        self.oldest_page += 1;
+        if let Some(last) = page.points.last() {
+            self.raise_history_floor(last.timestamp);
+        }
+        self.page_windows.retain(|window| window.page_index != page_index);
+        self.rebuild_page_window();
+        page_index
+    }
+
+    /// Records the extremes of `page` and spans the windowed extremes over
+    /// every open page.
+    fn update_page_window(&mut self, page: &MetricPage) {
+        let (min, max) = page
+            .points
+            .iter()
+            .fold((i64::MAX, i64::MIN), |(min, max), point| (min.min(point.value), max.max(point.value)));
+        let window = PageWindow {
+            page_index: page.page_index,
+            first_timestamp: page.points.first().map_or(0, |point| point.timestamp),
+            min,
+            max,
+        };
+        match self
+            .page_windows
+            .iter_mut()
+            .find(|window| window.page_index == page.page_index)
+        {
+            Some(existing) => *existing = window,
+            None => self.page_windows.push(window),
+        }
+        self.rebuild_page_window();
+    }
+
+    fn rebuild_page_window(&mut self) {
+        let last = self.aggregates.last;
+        let (min, max) = self
+            .page_windows
+            .iter()
+            .fold((last, last), |(min, max), window| (min.min(window.min), max.max(window.max)));
+        self.aggregates.window_min = min;
+        self.aggregates.window_max = max;
+    }
+
+    /// Whether a point at `timestamp` lands at or behind the latest recorded
+    /// point. Until a point is recorded `last_updated` is the creation time,
+    /// so no point is late.
+    pub fn is_late(&self, timestamp: i64) -> bool {
+        self.aggregates.count > 0 && timestamp <= self.last_updated
+    }
+
+    /// Marks the points up to `timestamp` as no longer retained.
+    fn raise_history_floor(&mut self, timestamp: i64) {
+        self.history_floor = self.history_floor.max(timestamp);
+    }
+
+    /// Marks the points up to the newest of `dropped` as no longer retained.
+    fn raise_history_floor_past(&mut self, dropped: &[MetricDataPoint]) {
+        if let Some(newest) = dropped.iter().map(|point| point.timestamp).max() {
+            self.raise_history_floor(newest);
+        }
+    }
+
     pub fn validate_value(&self, value: i64, timestamp: i64) -> Result<()> {
         require!(self.enabled, MonitoringError::MetricDisabled);
+
+        if self.params.kind == MetricKind::Counter {
+            require!(value >= 0, MonitoringError::NegativeCounterValue);
+        }
         
-        // Validate update timing
-        require!(
-            timestamp > self.last_updated,
-            MonitoringError::InvalidTimestamp
-        );
+        // The first point can't predate the metric itself
+        if self.aggregates.count == 0 {
+            require!(timestamp >= self.last_updated, MonitoringError::InvalidTimestamp);
+        }
+
+        // Points behind the latest one must fall within the late-arrival
+        // window and after every point that can no longer be deduplicated
+        let late = self.is_late(timestamp);
+        if late {
+            require!(
+                self.last_updated.abs_diff(timestamp) <= u64::from(self.params.late_arrival_window),
+                MonitoringError::LateArrivalOutsideWindow
+            );
+            require!(
+                timestamp > self.history_floor,
+                MonitoringError::LatePointNotRetained
+            );
+        }
 
         // Validate value is within configured bounds
         if let Some(min) = self.params.min_value {
@@ -90,15 +341,28 @@ impl MetricAccount {
             );
         }
 
-        // Validate rate of change if configured
-        if let Some(max_change) = self.params.max_rate_of_change {
-            if let Some(last_point) = self.data_points.last() {
-                let time_diff = timestamp - last_point.timestamp;
-                let value_diff = (value - last_point.value).abs();
-                let rate = if time_diff > 0 { value_diff as f64 / time_diff as f64 } else { 0.0 };
-                
+        // Validate rate of change if configured. Histogram observations are
+        // independent samples and late points are not compared against the
+        // latest one, so the check only applies to new gauge and counter values.
+        if let (Some(max_change), false) = (
+            self.params.max_rate_of_change,
+            late || matches!(self.params.kind, MetricKind::Histogram { .. }),
+        ) {
+            if let Some(last_value) = self.last_value() {
+                // Positive, since the timestamp was checked above
+                let time_diff = timestamp.abs_diff(self.last_updated);
+                let value_diff = match self.params.kind {
+                    // A counter that went backwards was reset and counted up from zero
+                    MetricKind::Counter if value < last_value => value.unsigned_abs(),
+                    _ => value.abs_diff(last_value),
+                };
+                // value_diff / time_diff <= max_change, without division
+                let allowed = u128::from(max_change)
+                    .checked_mul(u128::from(time_diff))
+                    .ok_or(MonitoringError::ArithmeticOverflow)?;
+
                 require!(
-                    rate <= max_change as f64,
+                    u128::from(value_diff) <= allowed,
                     MonitoringError::RateOfChangeExceeded
                 );
             }
@@ -106,25 +370,772 @@ impl MetricAccount {
 
         Ok(())
     }
+
+    /// Account size without inline data points. Every other field is
+    /// covered at its `max_len` bound.
+    pub const BASE_SPACE: usize =
+        8 + Self::INIT_SPACE - MAX_DATA_POINTS as usize * MetricDataPoint::INIT_SPACE;
+
+    /// Account size holding `points` inline data points.
+    pub fn space(points: usize) -> usize {
+        Self::BASE_SPACE + points * MetricDataPoint::INIT_SPACE
+    }
+
+    /// Account size needed once the next value is recorded, growing by one
+    /// point while inline history is below capacity.
+    pub fn space_after_record(&self, max_data_points: u32) -> usize {
+        let capacity = self.history_capacity(max_data_points);
+        let points = if self.params.store_history && self.history.is_none() && !self.is_paged() && capacity > 0 {
+            (self.data_points.len() + 1).min(capacity)
+        } else {
+            self.data_points.len()
+        };
+        Self::space(points)
+    }
+
+    /// Number of data points this metric may retain, bounded by the
+    /// global `max_data_points`. A `retention_period` of zero defers
+    /// entirely to the global limit.
+    pub fn history_capacity(&self, max_data_points: u32) -> usize {
+        let limit = max_data_points.min(MAX_DATA_POINTS);
+        let capacity = match self.params.retention_period {
+            0 => limit,
+            retention => retention.min(limit),
+        };
+        capacity as usize
+    }
+
+    /// Number of data points an attached zero-copy history retains:
+    /// `retention_period` when set, otherwise the whole buffer. The global
+    /// `max_data_points` only bounds inline history, which grows this account.
+    pub fn zero_copy_capacity(&self) -> u32 {
+        match self.params.retention_period {
+            0 => MetricHistory::CAPACITY,
+            retention => retention.min(MetricHistory::CAPACITY),
+        }
+    }
+
+    /// Attaches a freshly initialized zero-copy history, moving the inline
+    /// data points into it.
+    pub fn attach_history(&mut self, key: Pubkey, history: &mut MetricHistory) {
+        let capacity = self.zero_copy_capacity();
+        for point in std::mem::take(&mut self.data_points) {
+            let evicted = history.push(point, capacity);
+            self.raise_history_floor_past(&evicted);
+        }
+        self.aggregates.set_window(history.window());
+        self.history = Some(key);
+    }
+
+    /// Validates and records a new data point, evicting the oldest
+    /// retained point once the history is full.
+    pub fn record(
+        &mut self,
+        timestamp: i64,
+        value: i64,
+        max_data_points: u32,
+    ) -> Result<RecordOutcome> {
+        if self.is_late(timestamp) {
+            let index = self.data_points.partition_point(|point| point.timestamp < timestamp);
+            if timestamp == self.last_updated
+                || self.data_points.get(index).is_some_and(|point| point.timestamp == timestamp)
+            {
+                return Ok(RecordOutcome::Duplicate);
+            }
+            self.validate_value(value, timestamp)?;
+
+            self.commit(self.fold(timestamp, value)?);
+            let previous = index.checked_sub(1).map(|i| self.data_points[i].value);
+            let capacity = self.history_capacity(max_data_points);
+            if self.params.store_history && capacity > 0 {
+                // Once full, a point older than everything retained is not stored
+                if self.data_points.len() < capacity || index > 0 {
+                    self.data_points.insert(index, MetricDataPoint { timestamp, value });
+                } else {
+                    self.raise_history_floor(timestamp);
+                }
+                let excess = self.data_points.len().saturating_sub(capacity);
+                if excess > 0 {
+                    self.raise_history_floor(self.data_points[excess - 1].timestamp);
+                    self.data_points.drain(..excess);
+                }
+                self.aggregates
+                    .rebuild_window(self.data_points.iter().map(|point| point.value));
+            } else {
+                self.raise_history_floor(timestamp);
+            }
+            return Ok(RecordOutcome::Inserted { previous });
+        }
+        self.validate_value(value, timestamp)?;
+
+        self.commit(self.fold(timestamp, value)?);
+
+        let mut evicted = Vec::new();
+        let capacity = self.history_capacity(max_data_points);
+        if self.params.store_history && capacity > 0 {
+            if self.data_points.len() >= capacity {
+                let excess = self.data_points.len() + 1 - capacity;
+                self.raise_history_floor(self.data_points[excess - 1].timestamp);
+                evicted.extend(self.data_points.drain(..excess).map(|point| point.value));
+            }
+            self.data_points.push(MetricDataPoint { timestamp, value });
+        } else {
+            self.raise_history_floor(timestamp);
+        }
+
+        let retained = self.data_points.iter().map(|point| point.value);
+        self.aggregates
+            .update_window(value, self.params.store_history, &evicted, retained);
+        Ok(RecordOutcome::Appended)
+    }
+
+    /// Validates and records a new data point into an attached zero-copy
+    /// history account rather than the inline `data_points` vector.
+    pub fn record_to_history(
+        &mut self,
+        history: &mut MetricHistory,
+        timestamp: i64,
+        value: i64,
+    ) -> Result<RecordOutcome> {
+        let point = MetricDataPoint { timestamp, value };
+        if self.is_late(timestamp) {
+            let index = history.position(timestamp);
+            if timestamp == self.last_updated
+                || history.get(index).is_some_and(|point| point.timestamp == timestamp)
+            {
+                return Ok(RecordOutcome::Duplicate);
+            }
+            self.validate_value(value, timestamp)?;
+
+            self.commit(self.fold(timestamp, value)?);
+            let previous = index
+                .checked_sub(1)
+                .and_then(|i| history.get(i))
+                .map(|point| point.value);
+            if self.params.store_history {
+                let evicted = history.insert(point, self.zero_copy_capacity());
+                self.raise_history_floor_past(&evicted);
+                self.aggregates.set_window(history.window());
+            } else {
+                self.raise_history_floor(timestamp);
+            }
+            return Ok(RecordOutcome::Inserted { previous });
+        }
+        self.validate_value(value, timestamp)?;
+
+        self.commit(self.fold(timestamp, value)?);
+
+        if self.params.store_history {
+            let evicted = history.push(point, self.zero_copy_capacity());
+            self.raise_history_floor_past(&evicted);
+            self.aggregates.set_window(history.window());
+        } else {
+            self.raise_history_floor(timestamp);
+            self.aggregates.set_window(None);
+        }
+        Ok(RecordOutcome::Appended)
+    }
+
+    /// Points recorded before the returned time have outlived
+    /// `params.ttl_seconds`.
+    pub fn ttl_cutoff(&self, now: i64) -> i64 {
+        now.saturating_sub(i64::from(self.params.ttl_seconds))
+    }
+
+    /// Drops retained data points older than `params.ttl_seconds` relative
+    /// to `now`, returning how many were dropped.
+    pub fn prune(&mut self, now: i64) -> usize {
+        if self.params.ttl_seconds == 0 {
+            return 0;
+        }
+
+        let cutoff = self.ttl_cutoff(now);
+        // Points are kept in timestamp order
+        let expired = self
+            .data_points
+            .partition_point(|point| point.timestamp < cutoff);
+        if expired > 0 {
+            self.raise_history_floor(self.data_points[expired - 1].timestamp);
+            self.data_points.drain(..expired);
+            let last = self.aggregates.last;
+            let (min, max) = self
+                .data_points
+                .iter()
+                .fold((last, last), |(min, max), point| (min.min(point.value), max.max(point.value)));
+            self.aggregates.window_min = min;
+            self.aggregates.window_max = max;
+        }
+        expired
+    }
+
+    /// Drops the points of an attached zero-copy history older than
+    /// `params.ttl_seconds` relative to `now`, returning how many were
+    /// dropped.
+    pub fn prune_history(&mut self, history: &mut MetricHistory, now: i64) -> usize {
+        if self.params.ttl_seconds == 0 {
+            return 0;
+        }
+
+        let cutoff = self.ttl_cutoff(now);
+        let mut expired = 0;
+        while history.get(0).is_some_and(|point| point.timestamp < cutoff) {
+            if let Some(point) = history.pop_oldest() {
+                self.raise_history_floor(point.timestamp);
+            }
+            expired += 1;
+        }
+        if expired > 0 {
+            self.aggregates.set_window(history.window());
+        }
+        expired
+    }
+
+    /// Whether every point of `page` is older than `params.ttl_seconds`
+    /// relative to `now`. The page being written never expires.
+    pub fn page_expired(&self, page: &MetricPage, now: i64) -> bool {
+        self.params.ttl_seconds > 0
+            && page.page_index < self.current_page
+            && page
+                .points
+                .last()
+                .is_some_and(|point| point.timestamp < self.ttl_cutoff(now))
+    }
+
+    /// Validates and records a new data point into the history page
+    /// `page_index_for` routes it to, which starts a new page on rollover.
+    pub fn record_to_page(
+        &mut self,
+        page: &mut MetricPage,
+        timestamp: i64,
+        value: i64,
+    ) -> Result<RecordOutcome> {
+        require!(
+            page.page_index == self.page_index_for(timestamp),
+            MonitoringError::MetricPageMismatch
+        );
+
+        // Late points are kept in order within the page covering them
+        let index = page.points.partition_point(|point| point.timestamp < timestamp);
+        let outcome = if !self.is_late(timestamp) {
+            RecordOutcome::Appended
+        } else if timestamp == self.last_updated
+            || page.points.get(index).is_some_and(|point| point.timestamp == timestamp)
+        {
+            return Ok(RecordOutcome::Duplicate);
+        } else {
+            RecordOutcome::Inserted {
+                previous: index.checked_sub(1).map(|i| page.points[i].value),
+            }
+        };
+        self.validate_value(value, timestamp)?;
+        require!(page.points.len() < MAX_PAGE_POINTS, MonitoringError::MetricPageFull);
+        let rollover = page.page_index > self.current_page;
+        // At most one page beyond `max_pages` stays open, until the oldest
+        // one is closed
+        require!(
+            !rollover || self.open_pages() <= u64::from(self.params.max_pages),
+            MonitoringError::MetricPageNotClosed
+        );
+        self.commit(self.fold(timestamp, value)?);
+
+        if rollover {
+            self.current_page = page.page_index;
+            self.current_page_len = 0;
+        }
+        page.points.insert(index, MetricDataPoint { timestamp, value });
+        if page.page_index == self.current_page {
+            self.current_page_len += 1;
+        }
+
+        // Windowed extremes cover every open page
+        self.update_page_window(page);
+        Ok(outcome)
+    }
+
+    /// Folds a validated point into copies of the kind state and
+    /// aggregates, leaving the metric untouched so a point that fails
+    /// part-way can't half-apply. Late points only update the
+    /// order-independent state; the latest value, EWMA and counter rate keep
+    /// following the latest point.
+    fn fold(&self, timestamp: i64, value: i64) -> Result<FoldedPoint> {
+        let mut kind_state = self.kind_state.clone();
+        let mut aggregates = self.aggregates.clone();
+        let last_updated = if !self.is_late(timestamp) {
+            let elapsed = timestamp
+                .checked_sub(self.last_updated)
+                .ok_or(MonitoringError::ArithmeticOverflow)?;
+            kind_state.observe(&self.params.kind, value, Some(elapsed))?;
+            aggregates.observe(value, self.params.ewma_alpha())?;
+            timestamp
+        } else {
+            kind_state.observe(&self.params.kind, value, None)?;
+            aggregates.observe_late(value)?;
+            self.last_updated
+        };
+        Ok(FoldedPoint {
+            kind_state,
+            aggregates,
+            last_updated,
+        })
+    }
+
+    /// Applies the state computed by `fold`.
+    fn commit(&mut self, folded: FoldedPoint) {
+        self.kind_state = folded.kind_state;
+        self.aggregates = folded.aggregates;
+        self.last_updated = folded.last_updated;
+    }
+}
+
+/// Metric state after folding in a point, not yet applied
+struct FoldedPoint {
+    kind_state: MetricKindState,
+    aggregates: MetricAggregates,
+    last_updated: i64,
+}
+
+/// Counts `value` into its histogram bucket and the running sum and count.
+fn observe_histogram(
+    bucket_bounds: &[i64],
+    bucket_counts: &mut [u64],
+    sum: &mut i128,
+    count: &mut u64,
+    value: i64,
+) -> Result<()> {
+    let bucket = bucket_bounds
+        .iter()
+        .position(|bound| value <= *bound)
+        .unwrap_or(bucket_bounds.len());
+    let new_sum = sum
+        .checked_add(i128::from(value))
+        .ok_or(MonitoringError::ArithmeticOverflow)?;
+    let new_count = count
+        .checked_add(1)
+        .ok_or(MonitoringError::ArithmeticOverflow)?;
+
+    if let Some(bucket_count) = bucket_counts.get_mut(bucket) {
+        *bucket_count = bucket_count.saturating_add(1);
+    }
+    *sum = new_sum;
+    *count = new_count;
+    Ok(())
+}
+
+/// How a recorded point was applied to a metric
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordOutcome {
+    /// Newer than every recorded point
+    Appended,
+    /// Inserted behind the latest point, within the late-arrival window.
+    /// `previous` is the retained point just before it, if any.
+    Inserted { previous: Option<i64> },
+    /// A point with the same timestamp was already recorded
+    Duplicate,
+}
+
+/// Running summaries of a metric, updated on every record
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct MetricAggregates {
+    /// Number of values recorded
+    pub count: u64,
+    /// Sum of all recorded values
+    pub sum: i128,
+    /// Smallest value ever recorded
+    pub min: i64,
+    /// Largest value ever recorded
+    pub max: i64,
+    /// Most recently recorded value
+    pub last: i64,
+    /// Arithmetic mean of all recorded values
+    pub mean: i64,
+    /// Exponentially weighted moving average
+    pub ewma: i64,
+    /// Smallest value in the retained history
+    pub window_min: i64,
+    /// Largest value in the retained history
+    pub window_max: i64,
+}
+
+impl MetricAggregates {
+    /// Folds a value into the all-time aggregates, with `alpha_bps` as the
+    /// EWMA smoothing factor in basis points.
+    pub fn observe(&mut self, value: i64, alpha_bps: u16) -> Result<()> {
+        let count = self
+            .count
+            .checked_add(1)
+            .ok_or(MonitoringError::ArithmeticOverflow)?;
+        let sum = self
+            .sum
+            .checked_add(i128::from(value))
+            .ok_or(MonitoringError::ArithmeticOverflow)?;
+
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+            self.ewma = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+            let delta = i128::from(value) - i128::from(self.ewma);
+            let step = delta * i128::from(alpha_bps) / i128::from(BASIS_POINTS);
+            // The EWMA always lies between its previous value and `value`
+            self.ewma = i64::try_from(i128::from(self.ewma) + step)
+                .map_err(|_| MonitoringError::ArithmeticOverflow)?;
+        }
+
+        self.count = count;
+        self.sum = sum;
+        self.last = value;
+        self.mean = i64::try_from(sum / i128::from(count))
+            .map_err(|_| MonitoringError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Folds a point recorded behind the latest one into the
+    /// order-independent aggregates, leaving `last` and the EWMA alone.
+    pub fn observe_late(&mut self, value: i64) -> Result<()> {
+        let count = self
+            .count
+            .checked_add(1)
+            .ok_or(MonitoringError::ArithmeticOverflow)?;
+        let sum = self
+            .sum
+            .checked_add(i128::from(value))
+            .ok_or(MonitoringError::ArithmeticOverflow)?;
+
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.count = count;
+        self.sum = sum;
+        self.mean = i64::try_from(sum / i128::from(count))
+            .map_err(|_| MonitoringError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Recomputes the windowed extremes from the retained values, falling
+    /// back to the latest value when nothing is retained.
+    pub fn rebuild_window(&mut self, retained: impl Iterator<Item = i64>) {
+        let (min, max) = retained.fold((self.last, self.last), |(min, max), v| (min.min(v), max.max(v)));
+        self.window_min = min;
+        self.window_max = max;
+    }
+
+    /// Sets the windowed extremes, collapsing them to the latest value when
+    /// nothing is retained.
+    pub fn set_window(&mut self, window: Option<(i64, i64)>) {
+        (self.window_min, self.window_max) = window.unwrap_or((self.last, self.last));
+    }
+
+    /// Updates the windowed extremes after `value` was appended to the
+    /// retained history and `evicted` values dropped out of it. The window
+    /// is only rebuilt from `retained` when an evicted value was an extreme.
+    /// Without stored history the window collapses to the latest value.
+    pub fn update_window(
+        &mut self,
+        value: i64,
+        store_history: bool,
+        evicted: &[i64],
+        retained: impl Iterator<Item = i64>,
+    ) {
+        if self.count == 1 || !store_history {
+            self.window_min = value;
+            self.window_max = value;
+            return;
+        }
+
+        if evicted
+            .iter()
+            .any(|old| *old == self.window_min || *old == self.window_max)
+        {
+            let (min, max) = retained.fold((value, value), |(min, max), v| (min.min(v), max.max(v)));
+            self.window_min = min;
+            self.window_max = max;
+        } else {
+            self.window_min = self.window_min.min(value);
+            self.window_max = self.window_max.max(value);
+        }
+    }
+}
+
+/// How recorded values of a metric are interpreted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, InitSpace)]
+pub enum MetricKind {
+    /// Point-in-time value that may move freely
+    #[default]
+    Gauge,
+    /// Monotonically increasing total; a decrease is treated as a reset
+    Counter,
+    /// Distribution of observations over ascending bucket upper bounds
+    Histogram {
+        #[max_len(MAX_HISTOGRAM_BUCKETS)]
+        bucket_bounds: Vec<i64>,
+    },
+}
+
+/// State derived from the values recorded for a metric
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, InitSpace)]
+pub enum MetricKindState {
+    #[default]
+    Gauge,
+    Counter {
+        /// Last observed counter value
+        last_value: Option<i64>,
+        /// Number of detected counter resets
+        resets: u32,
+        /// Increase per second between the last two observations, as a
+        /// fixed-point number with `COUNTER_RATE_DECIMALS` decimals so slow
+        /// counters don't truncate to zero
+        rate_per_second: i64,
+    },
+    Histogram {
+        /// Observation counts per bucket, with a trailing overflow bucket
+        #[max_len(MAX_HISTOGRAM_COUNTS)]
+        bucket_counts: Vec<u64>,
+        /// Sum of all observations
+        sum: i128,
+        /// Number of observations
+        count: u64,
+    },
+}
+
+impl MetricKindState {
+    /// Folds a validated value recorded `elapsed` seconds after the latest
+    /// point, or behind it when `elapsed` is `None`. Only histograms count
+    /// late points.
+    pub fn observe(&mut self, kind: &MetricKind, value: i64, elapsed: Option<i64>) -> Result<()> {
+        match (self, kind) {
+            (
+                MetricKindState::Counter {
+                    last_value,
+                    resets,
+                    rate_per_second,
+                },
+                _,
+            ) => {
+                let Some(elapsed) = elapsed else {
+                    return Ok(());
+                };
+                *rate_per_second = match *last_value {
+                    Some(previous) => {
+                        require!(elapsed > 0, MonitoringError::InvalidTimestamp);
+                        let increase = if value >= previous {
+                            value - previous
+                        } else {
+                            *resets = resets.saturating_add(1);
+                            value
+                        };
+                        let scaled = i128::from(increase) * 10i128.pow(COUNTER_RATE_DECIMALS)
+                            / i128::from(elapsed);
+                        i64::try_from(scaled).map_err(|_| MonitoringError::ArithmeticOverflow)?
+                    }
+                    None => 0,
+                };
+                *last_value = Some(value);
+            }
+            (
+                MetricKindState::Histogram {
+                    bucket_counts,
+                    sum,
+                    count,
+                },
+                MetricKind::Histogram { bucket_bounds },
+            ) => observe_histogram(bucket_bounds, bucket_counts, sum, count, value)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn new(kind: &MetricKind) -> Self {
+        match kind {
+            MetricKind::Gauge => MetricKindState::Gauge,
+            MetricKind::Counter => MetricKindState::Counter {
+                last_value: None,
+                resets: 0,
+                rate_per_second: 0,
+            },
+            MetricKind::Histogram { bucket_bounds } => MetricKindState::Histogram {
+                bucket_counts: vec![0; bucket_bounds.len() + 1],
+                sum: 0,
+                count: 0,
+            },
+        }
+    }
 }
 
-/// Parameters configuring an individual metric
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+/// Parameters configuring an individual metric.
+///
+/// Values are fixed-point: a raw value `v` stands for `v / 10^scale` of
+/// `unit`. Bounds, rates and alert thresholds are all given in raw units.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
 pub struct MetricParams {
     /// Optional minimum allowed value
     pub min_value: Option<i64>,
     /// Optional maximum allowed value
     pub max_value: Option<i64>,
-    /// Optional maximum rate of change (units per second)
+    /// Optional maximum rate of change (raw units per second)
     pub max_rate_of_change: Option<u32>,
     /// Whether to store historical data points
     pub store_history: bool,
     /// Number of data points to retain (up to global max)
     pub retention_period: u32,
+    /// Age in seconds after which retained points may be pruned (0 keeps
+    /// them until evicted by `retention_period`)
+    pub ttl_seconds: u32,
+    /// How recorded values are interpreted
+    pub kind: MetricKind,
+    /// EWMA smoothing factor in basis points (0 selects the default)
+    pub ewma_alpha_bps: u16,
+    /// On-chain account the value is sampled from, instead of a reporter
+    pub source: Option<MetricSource>,
+    /// Number of decimal places in recorded values
+    pub scale: u8,
+    /// Display unit of the scaled value, e.g. `ms`
+    #[max_len(MAX_UNIT_LENGTH)]
+    pub unit: String,
+    /// Number of `MetricPage` accounts to retain (0 keeps history inline)
+    pub max_pages: u32,
+    /// Coarse rollup tiers every recorded point is folded into
+    #[max_len(MAX_ROLLUP_TIERS)]
+    pub rollups: Vec<RollupTier>,
+    /// How many seconds behind the latest point a late point may arrive
+    pub late_arrival_window: u32,
+}
+
+impl MetricParams {
+    pub fn ewma_alpha(&self) -> u16 {
+        match self.ewma_alpha_bps {
+            0 => DEFAULT_EWMA_ALPHA_BPS,
+            alpha => alpha,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.ewma_alpha_bps <= BASIS_POINTS,
+            MonitoringError::InvalidEwmaAlpha
+        );
+        require!(self.scale <= MAX_SCALE, MonitoringError::InvalidScale);
+        require!(
+            self.unit.len() <= MAX_UNIT_LENGTH,
+            MonitoringError::UnitTooLong
+        );
+        require!(
+            self.rollups.len() <= MAX_ROLLUP_TIERS,
+            MonitoringError::InvalidRollupTiers
+        );
+        for (i, tier) in self.rollups.iter().enumerate() {
+            require!(
+                tier.retention > 0
+                    && usize::from(tier.retention) <= MAX_ROLLUP_BUCKETS
+                    && self.rollups[..i].iter().all(|other| other.interval != tier.interval),
+                MonitoringError::InvalidRollupTiers
+            );
+        }
+        if let MetricKind::Histogram { bucket_bounds } = &self.kind {
+            require!(
+                !bucket_bounds.is_empty() && bucket_bounds.len() <= MAX_HISTOGRAM_BUCKETS,
+                MonitoringError::InvalidHistogramBuckets
+            );
+            require!(
+                bucket_bounds.windows(2).all(|pair| pair[0] < pair[1]),
+                MonitoringError::InvalidHistogramBuckets
+            );
+        }
+        if let Some(source) = &self.source {
+            source.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// On-chain account a sourced metric samples its value from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct MetricSource {
+    /// Account the value is read from
+    pub account: Pubkey,
+    /// Which part of the account holds the value
+    pub field: SourceField,
+    /// Minimum seconds between samples, since anyone may sample
+    pub sample_interval: u32,
+}
+
+/// Location of a sampled value within the source account
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub enum SourceField {
+    /// The account's lamport balance
+    Lamports,
+    /// A little-endian integer of `width` bytes at `offset` in the account
+    /// data, counted after the 8-byte discriminator if `skip_discriminator`
+    Integer {
+        offset: u32,
+        width: u8,
+        signed: bool,
+        skip_discriminator: bool,
+    },
+    /// The amount held by an SPL Token or Token-2022 token account
+    TokenBalance,
+    /// The supply of an SPL Token or Token-2022 mint
+    MintSupply,
+}
+
+impl MetricSource {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.sample_interval >= MIN_SAMPLE_INTERVAL,
+            MonitoringError::InvalidUpdateInterval
+        );
+        if let SourceField::Integer { width, .. } = self.field {
+            require!(
+                matches!(width, 1 | 2 | 4 | 8),
+                MonitoringError::InvalidMetricSource
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads the current value from the source account. Token sources are
+    /// read through `sample_token_metric` instead.
+    pub fn read(&self, info: &AccountInfo) -> Result<i64> {
+        match self.field {
+            SourceField::TokenBalance | SourceField::MintSupply => {
+                err!(MonitoringError::TokenSourceNotSupported)
+            }
+            SourceField::Lamports => {
+                i64::try_from(info.lamports()).map_err(|_| MonitoringError::SourceValueOverflow.into())
+            }
+            SourceField::Integer {
+                offset,
+                width,
+                signed,
+                skip_discriminator,
+            } => {
+                let width = usize::from(width);
+                let start = (offset as usize)
+                    .checked_add(if skip_discriminator { 8 } else { 0 })
+                    .ok_or(MonitoringError::SourceOutOfBounds)?;
+                let end = start.checked_add(width).ok_or(MonitoringError::SourceOutOfBounds)?;
+
+                let data = info.try_borrow_data()?;
+                let bytes = data.get(start..end).ok_or(MonitoringError::SourceOutOfBounds)?;
+                let mut buf = [0u8; 8];
+                buf[..width].copy_from_slice(bytes);
+
+                if signed {
+                    // Sign-extend from the top bit of the field
+                    let shift = 64 - 8 * width as u32;
+                    Ok(i64::from_le_bytes(buf) << shift >> shift)
+                } else {
+                    i64::try_from(u64::from_le_bytes(buf))
+                        .map_err(|_| MonitoringError::SourceValueOverflow.into())
+                }
+            }
+        }
+    }
 }
 
 /// A single data point for a metric
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, InitSpace)]
+#[repr(C)]
 pub struct MetricDataPoint {
     /// Unix timestamp when the data point was recorded
     pub timestamp: i64,
@@ -132,66 +1143,1851 @@ pub struct MetricDataPoint {
     pub value: i64,
 }
 
-/// Alert configuration account
+/// Fixed-size page of a metric's history, addressed by
+/// `[b"metric_page", metric, page_index]`
 #[account]
-#[derive(Default)]
-pub struct AlertConfig {
-    /// Authority allowed to manage this alert
-    pub authority: Pubkey,
-    /// The metric this alert monitors
+#[derive(Default, InitSpace)]
+pub struct MetricPage {
+    /// Metric this page belongs to
     pub metric: Pubkey,
-    /// Alert configuration parameters 
-    pub params: AlertConfigParams,
-    /// Whether the alert is currently enabled
-    pub enabled: bool,
-    /// Last time the alert was triggered (unix timestamp)
-    pub last_triggered: i64,
+    /// Position of this page in the metric's history
+    pub page_index: u64,
+    /// Points recorded into this page, up to `MAX_PAGE_POINTS`
+    #[max_len(MAX_PAGE_POINTS)]
+    pub points: Vec<MetricDataPoint>,
 }
 
-/// Parameters configuring an alert
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
-pub struct AlertConfigParams {
-    /// Threshold type (above/below/change)
-    pub threshold_type: AlertThresholdType,
-    /// Threshold value that triggers the alert
-    pub threshold_value: i64,
-    /// Optional secondary threshold for range-based alerts
-    pub secondary_threshold: Option<i64>,
-    /// Minimum time between alert triggers (seconds)
-    pub min_trigger_interval: u32,
-    /// Number of consecutive violations before triggering
-    pub required_violations: u8,
-    /// Optional webhook URL for notifications
-    pub webhook_url: Option<String>,
+/// Extremes of the points in one open history page
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, InitSpace)]
+pub struct PageWindow {
+    pub page_index: u64,
+    pub first_timestamp: i64,
+    pub min: i64,
+    pub max: i64,
 }
 
-/// Types of alert thresholds
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
-pub enum AlertThresholdType {
+/// A rollup tier of a metric: bucket width and how many buckets to keep
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct RollupTier {
+    pub interval: RollupInterval,
+    pub retention: u16,
+}
+
+/// Width of the buckets in a rollup tier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, InitSpace)]
+pub enum RollupInterval {
     #[default]
-    Above,
-    Below,
-    Change,
-    Range,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl RollupInterval {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            RollupInterval::Minute => 60,
+            RollupInterval::Hour => 3_600,
+            RollupInterval::Day => 86_400,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`.
+    pub fn bucket_start(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.seconds())
+    }
+}
+
+/// Per-bucket aggregates of one rollup tier of a metric, addressed by
+/// `[b"rollup", metric, interval seconds]`
+#[account]
+#[derive(Default, InitSpace)]
+pub struct MetricRollup {
+    /// Metric this rollup belongs to
+    pub metric: Pubkey,
+    /// Bucket width of this tier
+    pub interval: RollupInterval,
+    /// Buckets ordered by start time, oldest first
+    #[max_len(MAX_ROLLUP_BUCKETS)]
+    pub buckets: Vec<RollupBucket>,
 }
 
-/// Custom error types for the monitoring program
-#[error_code]
-pub enum MonitoringError {
-    #[msg("Invalid maximum metrics configuration")]
-    InvalidMaxMetrics,
-    #[msg("Invalid maximum alerts configuration")]
-    InvalidMaxAlerts,
-    #[msg("Invalid maximum data points configuration")]
-    InvalidMaxDataPoints,
-    #[msg("Metric is currently disabled")]
-    MetricDisabled,
-    #[msg("Invalid timestamp")]
-    InvalidTimestamp,
-    #[msg("Value below configured minimum")]
-    ValueBelowMinimum,
-    #[msg("Value above configured maximum")]
-    ValueAboveMaximum,
-    #[msg("Rate of change exceeded configured maximum")]
-    RateOfChangeExceeded,
-}
\ No newline at end of file
+impl MetricRollup {
+    /// Folds a point into its bucket, creating the bucket if needed and
+    /// dropping the oldest buckets beyond `retention`. Points older than
+    /// every retained bucket of a full tier are ignored.
+    pub fn fold(&mut self, timestamp: i64, value: i64, retention: u16) -> Result<()> {
+        let start = self.interval.bucket_start(timestamp);
+        match self.buckets.binary_search_by_key(&start, |bucket| bucket.start) {
+            Ok(i) => self.buckets[i].observe(timestamp, value)?,
+            Err(i) => {
+                let full = self.buckets.len() >= usize::from(retention);
+                if i == 0 && full {
+                    return Ok(());
+                }
+                self.buckets.insert(i, RollupBucket::new(start, timestamp, value));
+            }
+        }
+
+        let excess = self.buckets.len().saturating_sub(usize::from(retention));
+        self.buckets.drain(..excess);
+        Ok(())
+    }
+}
+
+/// Aggregates of the points falling into one bucket
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, InitSpace)]
+pub struct RollupBucket {
+    /// Start of the bucket (unix timestamp)
+    pub start: i64,
+    /// Number of points in the bucket
+    pub count: u64,
+    /// Sum of the points in the bucket
+    pub sum: i128,
+    /// Smallest point in the bucket
+    pub min: i64,
+    /// Largest point in the bucket
+    pub max: i64,
+    /// Latest point in the bucket
+    pub last: i64,
+    /// Timestamp of `last`
+    pub last_at: i64,
+}
+
+impl RollupBucket {
+    pub fn new(start: i64, timestamp: i64, value: i64) -> Self {
+        Self {
+            start,
+            count: 1,
+            sum: i128::from(value),
+            min: value,
+            max: value,
+            last: value,
+            last_at: timestamp,
+        }
+    }
+
+    pub fn observe(&mut self, timestamp: i64, value: i64) -> Result<()> {
+        self.count = self
+            .count
+            .checked_add(1)
+            .ok_or(MonitoringError::ArithmeticOverflow)?;
+        self.sum = self
+            .sum
+            .checked_add(i128::from(value))
+            .ok_or(MonitoringError::ArithmeticOverflow)?;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if timestamp >= self.last_at {
+            self.last = value;
+            self.last_at = timestamp;
+        }
+        Ok(())
+    }
+}
+
+/// Zero-copy circular buffer of data points for a single metric.
+///
+/// The account is too large to be created through a CPI, so clients
+/// allocate `8 + size_of::<MetricHistory>()` bytes owned by the program
+/// before calling `initialize_metric_history`.
+///
+/// Two monotonic queues track the points that can still become the window
+/// minimum or maximum, so appending and evicting keep the windowed extremes
+/// in amortized O(1). Only late inserts, which shift points, rebuild them.
+#[account(zero_copy)]
+pub struct MetricHistory {
+    /// The metric this history belongs to
+    pub metric: Pubkey,
+    /// Index of the oldest retained data point
+    pub head: u32,
+    /// Number of data points currently retained
+    pub len: u32,
+    /// Ring buffer storage
+    pub points: [MetricDataPoint; MAX_HISTORY_POINTS as usize],
+    /// Candidates for the window minimum, with ascending values
+    pub min_queue: WindowQueue,
+    /// Candidates for the window maximum, with descending values
+    pub max_queue: WindowQueue,
+}
+
+impl MetricHistory {
+    pub const CAPACITY: u32 = MAX_HISTORY_POINTS;
+
+    /// Appends a data point, evicting the oldest ones so that at most
+    /// `capacity` points are retained. Returns the evicted points.
+    pub fn push(&mut self, point: MetricDataPoint, capacity: u32) -> Vec<MetricDataPoint> {
+        let capacity = capacity.clamp(1, Self::CAPACITY);
+        let mut evicted = Vec::new();
+        while self.len >= capacity {
+            evicted.extend(self.pop_oldest());
+        }
+
+        let index = self.index(self.len);
+        self.points[index] = point;
+        self.len += 1;
+        self.enqueue(index);
+        evicted
+    }
+
+    /// Removes and returns the oldest retained data point.
+    pub fn pop_oldest(&mut self) -> Option<MetricDataPoint> {
+        let oldest = *self.get(0)?;
+        let index = self.index(0);
+        // The oldest point can only be at the front of either queue
+        if self.min_queue.front() == Some(index) {
+            self.min_queue.pop_front();
+        }
+        if self.max_queue.front() == Some(index) {
+            self.max_queue.pop_front();
+        }
+        self.head = (self.head + 1) % Self::CAPACITY;
+        self.len -= 1;
+        Some(oldest)
+    }
+
+    /// Smallest and largest retained values.
+    pub fn window(&self) -> Option<(i64, i64)> {
+        let min = self.points[self.min_queue.front()?].value;
+        let max = self.points[self.max_queue.front()?].value;
+        Some((min, max))
+    }
+
+    /// Adds the point at physical `index`, the newest one, to both queues,
+    /// dropping the candidates it supersedes.
+    fn enqueue(&mut self, index: usize) {
+        let value = self.points[index].value;
+        while self
+            .min_queue
+            .back()
+            .is_some_and(|back| self.points[back].value >= value)
+        {
+            self.min_queue.pop_back();
+        }
+        self.min_queue.push_back(index);
+
+        while self
+            .max_queue
+            .back()
+            .is_some_and(|back| self.points[back].value <= value)
+        {
+            self.max_queue.pop_back();
+        }
+        self.max_queue.push_back(index);
+    }
+
+    /// Rebuilds both queues from the retained points.
+    fn rebuild_queues(&mut self) {
+        self.min_queue.clear();
+        self.max_queue.clear();
+        for i in 0..self.len {
+            self.enqueue(self.index(i));
+        }
+    }
+
+    /// Inserts a data point in timestamp order, evicting the oldest ones so
+    /// that at most `capacity` points are retained. A point older than every
+    /// retained point of a full buffer is not stored and is returned as
+    /// evicted itself.
+    pub fn insert(&mut self, point: MetricDataPoint, capacity: u32) -> Vec<MetricDataPoint> {
+        if self.len >= capacity.clamp(1, Self::CAPACITY)
+            && self.get(0).is_some_and(|oldest| point.timestamp < oldest.timestamp)
+        {
+            return vec![point];
+        }
+
+        let evicted = self.push(point, capacity);
+        // Move the new point back past every newer one
+        let mut i = self.len - 1;
+        while i > 0 && self.points[self.index(i - 1)].timestamp > point.timestamp {
+            let (newer, older) = (self.index(i), self.index(i - 1));
+            self.points.swap(newer, older);
+            i -= 1;
+        }
+        self.rebuild_queues();
+        evicted
+    }
+
+    /// Number of retained data points older than `timestamp`.
+    pub fn position(&self, timestamp: i64) -> u32 {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.points[self.index(mid)].timestamp < timestamp {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// Whether a retained data point has exactly `timestamp`.
+    pub fn contains(&self, timestamp: i64) -> bool {
+        self.get(self.position(timestamp))
+            .is_some_and(|point| point.timestamp == timestamp)
+    }
+
+    /// Returns the `i`-th retained data point, oldest first.
+    pub fn get(&self, i: u32) -> Option<&MetricDataPoint> {
+        if i >= self.len {
+            return None;
+        }
+        Some(&self.points[self.index(i)])
+    }
+
+    /// Retained data points, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &MetricDataPoint> + '_ {
+        (0..self.len).filter_map(|i| self.get(i))
+    }
+
+    fn index(&self, i: u32) -> usize {
+        ((self.head + i) % Self::CAPACITY) as usize
+    }
+
+    /// Returns the most recently recorded data point.
+    pub fn latest(&self) -> Option<&MetricDataPoint> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+}
+
+/// Fixed-capacity double-ended queue of physical `MetricHistory` indices
+#[zero_copy]
+pub struct WindowQueue {
+    /// Ring buffer storage
+    pub indices: [u16; MAX_HISTORY_POINTS as usize],
+    /// Position of the front entry
+    pub head: u16,
+    /// Number of entries
+    pub len: u16,
+}
+
+impl WindowQueue {
+    const CAPACITY: usize = MAX_HISTORY_POINTS as usize;
+
+    pub fn front(&self) -> Option<usize> {
+        (self.len > 0).then(|| usize::from(self.indices[usize::from(self.head)]))
+    }
+
+    pub fn back(&self) -> Option<usize> {
+        let last = usize::from(self.len).checked_sub(1)?;
+        Some(usize::from(self.indices[self.slot(last)]))
+    }
+
+    pub fn pop_front(&mut self) {
+        if self.len > 0 {
+            self.head = ((usize::from(self.head) + 1) % Self::CAPACITY) as u16;
+            self.len -= 1;
+        }
+    }
+
+    pub fn pop_back(&mut self) {
+        self.len = self.len.saturating_sub(1);
+    }
+
+    /// Appends an index. Entries are distinct retained points, so the
+    /// queue never holds more than `CAPACITY` of them.
+    pub fn push_back(&mut self, index: usize) {
+        let slot = self.slot(usize::from(self.len));
+        self.indices[slot] = index as u16;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    fn slot(&self, i: usize) -> usize {
+        (usize::from(self.head) + i) % Self::CAPACITY
+    }
+}
+
+/// Alert configuration account
+#[account]
+#[derive(Default, InitSpace)]
+pub struct AlertConfig {
+    /// Monitoring state this alert belongs to
+    pub monitoring_state: Pubkey,
+    /// Authority allowed to manage this alert
+    pub authority: Pubkey,
+    /// The metric this alert monitors
+    pub metric: Pubkey,
+    /// Per-metric id of this alert, used in its PDA seeds
+    pub alert_id: u16,
+    /// Alert configuration parameters 
+    pub params: AlertConfigParams,
+    /// Whether the alert is currently enabled
+    pub enabled: bool,
+    /// Last time the alert was triggered (unix timestamp)
+    pub last_triggered: i64,
+    /// Number of consecutive recorded values that violated the threshold
+    pub consecutive_violations: u8,
+    /// Number of consecutive recovered values while firing
+    pub consecutive_recoveries: u8,
+    /// Current position in the alert lifecycle
+    pub state: AlertState,
+    /// When the alert last started firing (unix timestamp)
+    pub firing_since: i64,
+    /// When the alert last stopped firing (unix timestamp)
+    pub resolved_at: i64,
+    /// Who acknowledged the current firing, if anyone
+    pub acknowledged_by: Option<Pubkey>,
+    /// When the current firing was acknowledged (unix timestamp)
+    pub acknowledged_at: i64,
+    /// The alert will not start firing before this time (unix timestamp)
+    pub snoozed_until: i64,
+}
+
+impl AlertConfig {
+    /// A fresh, enabled alert in the `Ok` state.
+    pub fn new(
+        monitoring_state: Pubkey,
+        authority: Pubkey,
+        metric: Pubkey,
+        alert_id: u16,
+        params: AlertConfigParams,
+    ) -> Self {
+        Self {
+            monitoring_state,
+            authority,
+            metric,
+            alert_id,
+            params,
+            enabled: true,
+            last_triggered: 0,
+            consecutive_violations: 0,
+            consecutive_recoveries: 0,
+            state: AlertState::Ok,
+            firing_since: 0,
+            resolved_at: 0,
+            acknowledged_by: None,
+            acknowledged_at: 0,
+            snoozed_until: 0,
+        }
+    }
+
+    /// Whether `value` violates the configured threshold. `previous` is the
+    /// last recorded value of the metric, used by `Change` alerts.
+    pub fn is_violation(&self, value: i64, previous: Option<i64>) -> bool {
+        let params = &self.params;
+        match params.threshold_type {
+            AlertThresholdType::Above => value > params.threshold_value,
+            AlertThresholdType::Below => value < params.threshold_value,
+            AlertThresholdType::Change => previous.is_some_and(|previous| {
+                value.abs_diff(previous) > params.threshold_value.unsigned_abs()
+            }),
+            AlertThresholdType::Range => {
+                let upper = params.secondary_threshold.unwrap_or(params.threshold_value);
+                value < params.threshold_value || value > upper
+            }
+            // Neither kind is driven by recorded values
+            AlertThresholdType::Stale | AlertThresholdType::AccountChange => false,
+        }
+    }
+
+    /// Whether `value` has cleared the threshold by at least the hysteresis
+    /// band, so that it counts towards resolving a firing alert.
+    pub fn is_recovered(&self, value: i64, previous: Option<i64>) -> bool {
+        let params = &self.params;
+        let band = params.hysteresis;
+        match params.threshold_type {
+            AlertThresholdType::Above => value <= params.threshold_value.saturating_sub(band),
+            AlertThresholdType::Below => value >= params.threshold_value.saturating_add(band),
+            AlertThresholdType::Change => match previous {
+                Some(previous) => {
+                    value.abs_diff(previous)
+                        <= params
+                            .threshold_value
+                            .unsigned_abs()
+                            .saturating_sub(band.unsigned_abs())
+                }
+                None => true,
+            },
+            AlertThresholdType::Range => {
+                let upper = params.secondary_threshold.unwrap_or(params.threshold_value);
+                value >= params.threshold_value.saturating_add(band)
+                    && value <= upper.saturating_sub(band)
+            }
+            AlertThresholdType::Stale | AlertThresholdType::AccountChange => true,
+        }
+    }
+
+    /// Evaluates a newly recorded value against the alert. While
+    /// `suppressed` the alert keeps counting violations but cannot start
+    /// firing.
+    pub fn evaluate(
+        &mut self,
+        value: i64,
+        previous: Option<i64>,
+        now: i64,
+        suppressed: bool,
+    ) -> AlertTransition {
+        let violated = self.is_violation(value, previous);
+        let recovered = !violated && self.is_recovered(value, previous);
+        self.advance(violated, recovered, now, suppressed || self.is_snoozed(now))
+    }
+
+    /// Evaluates a value inserted behind the metric's latest point. A
+    /// violation counts towards firing, but a late value within the
+    /// threshold leaves the alert as the latest value set it.
+    pub fn evaluate_late(
+        &mut self,
+        value: i64,
+        previous: Option<i64>,
+        now: i64,
+        suppressed: bool,
+    ) -> AlertTransition {
+        if !self.is_violation(value, previous) {
+            return AlertTransition::Unchanged;
+        }
+        self.advance(true, false, now, suppressed || self.is_snoozed(now))
+    }
+
+    /// Seconds since the metric was last updated.
+    pub fn staleness(last_updated: i64, now: i64) -> i64 {
+        now.saturating_sub(last_updated)
+    }
+
+    /// Evaluates a `Stale` alert against the time of the metric's last
+    /// update. Since anyone may check, violations are counted in whole
+    /// `threshold_value` periods without an update rather than per check.
+    pub fn evaluate_staleness(
+        &mut self,
+        last_updated: i64,
+        now: i64,
+        suppressed: bool,
+    ) -> AlertTransition {
+        let staleness = Self::staleness(last_updated, now);
+        let stale = staleness > self.params.threshold_value;
+        if stale {
+            let periods = staleness / self.params.threshold_value.max(1);
+            // `advance` counts this check as the latest violation
+            self.consecutive_violations = u8::try_from(periods).unwrap_or(u8::MAX).saturating_sub(1);
+        }
+        self.advance(stale, !stale, now, suppressed || self.is_snoozed(now))
+    }
+
+    /// Evaluates an `AccountChange` alert against the outcome of an account
+    /// watch check. Every check sees the same change until the baseline is
+    /// reset, so a single one is enough to fire and `required_violations`
+    /// is ignored. Only resetting the baseline resolves the alert.
+    pub fn evaluate_account_change(
+        &mut self,
+        changed: bool,
+        now: i64,
+        suppressed: bool,
+    ) -> AlertTransition {
+        if !changed {
+            return AlertTransition::Unchanged;
+        }
+        self.consecutive_violations = self.params.required_violations.saturating_sub(1);
+        self.advance(true, false, now, suppressed || self.is_snoozed(now))
+    }
+
+    pub fn is_snoozed(&self, now: i64) -> bool {
+        now < self.snoozed_until
+    }
+
+    /// Advances the alert state machine by one observation.
+    ///
+    /// Violations move the alert to `Pending` until `required_violations`
+    /// consecutive violations have been seen and the `min_trigger_interval`
+    /// cooldown has elapsed, at which point it starts `Firing`. A firing
+    /// alert is `Resolved` after `recovery_count` consecutive observations
+    /// outside the hysteresis band. A `suppressed` alert stays `Pending`.
+    pub fn advance(
+        &mut self,
+        violated: bool,
+        recovered: bool,
+        now: i64,
+        suppressed: bool,
+    ) -> AlertTransition {
+        if !self.enabled {
+            return AlertTransition::Unchanged;
+        }
+
+        if violated {
+            self.consecutive_violations = self.consecutive_violations.saturating_add(1);
+            self.consecutive_recoveries = 0;
+            if self.state == AlertState::Firing {
+                return AlertTransition::Unchanged;
+            }
+
+            let cooled_down = self.last_triggered == 0
+                || now - self.last_triggered >= i64::from(self.params.min_trigger_interval);
+            if self.consecutive_violations >= self.params.required_violations.max(1)
+                && cooled_down
+                && !suppressed
+            {
+                self.state = AlertState::Firing;
+                self.firing_since = now;
+                self.last_triggered = now;
+                self.acknowledged_by = None;
+                self.acknowledged_at = 0;
+                return AlertTransition::Triggered;
+            }
+
+            self.state = AlertState::Pending;
+            return AlertTransition::Unchanged;
+        }
+
+        self.consecutive_violations = 0;
+        match self.state {
+            AlertState::Pending => self.state = AlertState::Ok,
+            AlertState::Firing if recovered => {
+                self.consecutive_recoveries = self.consecutive_recoveries.saturating_add(1);
+                if self.consecutive_recoveries >= self.params.recovery_count.max(1) {
+                    self.state = AlertState::Resolved;
+                    self.resolved_at = now;
+                    self.consecutive_recoveries = 0;
+                    self.acknowledged_by = None;
+                    return AlertTransition::Resolved;
+                }
+            }
+            // Still inside the hysteresis band
+            AlertState::Firing => self.consecutive_recoveries = 0,
+            AlertState::Ok | AlertState::Resolved => {}
+        }
+        AlertTransition::Unchanged
+    }
+
+    /// Returns the alert to a clean state. A firing alert is resolved at
+    /// `now` rather than silently dropped back to `Ok`.
+    pub fn reset(&mut self, now: i64) -> AlertTransition {
+        let transition = if self.state == AlertState::Firing {
+            self.state = AlertState::Resolved;
+            self.resolved_at = now;
+            self.acknowledged_by = None;
+            AlertTransition::Resolved
+        } else {
+            self.state = AlertState::Ok;
+            AlertTransition::Unchanged
+        };
+        self.consecutive_violations = 0;
+        self.consecutive_recoveries = 0;
+        transition
+    }
+}
+
+/// Lifecycle state of an alert
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, InitSpace)]
+pub enum AlertState {
+    /// No active violations
+    #[default]
+    Ok,
+    /// Violating, but not yet for long enough to fire
+    Pending,
+    /// Actively firing
+    Firing,
+    /// Was firing and has since recovered
+    Resolved,
+}
+
+/// Outcome of evaluating an alert against a recorded value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlertTransition {
+    Unchanged,
+    Triggered,
+    Resolved,
+}
+
+/// Alert combining several alerts or metric conditions
+#[account]
+#[derive(Default, InitSpace)]
+pub struct CompositeAlert {
+    /// Monitoring state this composite alert belongs to
+    pub monitoring_state: Pubkey,
+    /// Authority allowed to manage this composite alert
+    pub authority: Pubkey,
+    /// Per-monitoring-state id, used in its PDA seeds
+    pub composite_id: u32,
+    /// How the conditions are combined
+    pub operator: CompositeOperator,
+    /// Conditions referencing existing alerts or metrics
+    #[max_len(MAX_COMPOSITE_CONDITIONS)]
+    pub conditions: Vec<CompositeCondition>,
+    /// Whether the composite alert is currently enabled
+    pub enabled: bool,
+    /// Current position in the alert lifecycle
+    pub state: AlertState,
+    /// When the composite alert last started firing (unix timestamp)
+    pub firing_since: i64,
+    /// When the composite alert last stopped firing (unix timestamp)
+    pub resolved_at: i64,
+    /// Metrics the conditions depend on, directly or through an alert
+    #[max_len(MAX_COMPOSITE_CONDITIONS)]
+    pub metrics: Vec<Pubkey>,
+    /// Who acknowledged the current firing, if anyone
+    pub acknowledged_by: Option<Pubkey>,
+    /// When the current firing was acknowledged (unix timestamp)
+    pub acknowledged_at: i64,
+    /// The composite alert will not start firing before this time (unix timestamp)
+    pub snoozed_until: i64,
+}
+
+impl CompositeAlert {
+    /// Combines the conditions according to the operator. Returns `None`
+    /// if `resolve` could not determine every condition.
+    pub fn is_satisfied(&self, resolve: impl Fn(&CompositeCondition) -> Option<bool>) -> Option<bool> {
+        let mut met = 0;
+        for condition in &self.conditions {
+            if resolve(condition)? {
+                met += 1;
+            }
+        }
+
+        Some(match self.operator {
+            CompositeOperator::And => met == self.conditions.len(),
+            CompositeOperator::Or => met > 0,
+            CompositeOperator::Not => met == 0,
+            CompositeOperator::AtLeast { count } => met >= usize::from(count),
+        })
+    }
+
+    pub fn is_snoozed(&self, now: i64) -> bool {
+        now < self.snoozed_until
+    }
+
+    /// Fires when the combined condition starts holding and resolves when
+    /// it stops. While `suppressed` or snoozed the composite alert cannot
+    /// start firing, but a firing one still resolves.
+    pub fn advance(&mut self, satisfied: bool, now: i64, suppressed: bool) -> AlertTransition {
+        if !self.enabled {
+            return AlertTransition::Unchanged;
+        }
+
+        match (satisfied, self.state) {
+            (true, AlertState::Firing) => AlertTransition::Unchanged,
+            (true, _) if suppressed || self.is_snoozed(now) => AlertTransition::Unchanged,
+            (true, _) => {
+                self.state = AlertState::Firing;
+                self.firing_since = now;
+                self.acknowledged_by = None;
+                self.acknowledged_at = 0;
+                AlertTransition::Triggered
+            }
+            (false, AlertState::Firing) => {
+                self.state = AlertState::Resolved;
+                self.resolved_at = now;
+                self.acknowledged_by = None;
+                AlertTransition::Resolved
+            }
+            (false, _) => AlertTransition::Unchanged,
+        }
+    }
+}
+
+/// How the conditions of a composite alert are combined
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, InitSpace)]
+pub enum CompositeOperator {
+    /// Every condition holds
+    #[default]
+    And,
+    /// At least one condition holds
+    Or,
+    /// No condition holds
+    Not,
+    /// At least `count` conditions hold
+    AtLeast { count: u8 },
+}
+
+/// A single input to a composite alert
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub enum CompositeCondition {
+    /// Holds while the referenced alert is firing
+    Alert { alert: Pubkey },
+    /// Holds while the metric's latest value crosses the threshold
+    Metric {
+        metric: Pubkey,
+        threshold_type: AlertThresholdType,
+        threshold_value: i64,
+        secondary_threshold: Option<i64>,
+    },
+}
+
+impl CompositeCondition {
+    /// The alert or metric account this condition references.
+    pub fn account(&self) -> Pubkey {
+        match self {
+            CompositeCondition::Alert { alert } => *alert,
+            CompositeCondition::Metric { metric, .. } => *metric,
+        }
+    }
+
+    /// Whether a metric condition holds for `value`. Only value thresholds
+    /// (`Above`, `Below` and `Range`) are meaningful here.
+    pub fn is_met_by(&self, value: i64) -> bool {
+        match self {
+            CompositeCondition::Alert { .. } => false,
+            CompositeCondition::Metric {
+                threshold_type,
+                threshold_value,
+                secondary_threshold,
+                ..
+            } => match threshold_type {
+                AlertThresholdType::Above => value > *threshold_value,
+                AlertThresholdType::Below => value < *threshold_value,
+                AlertThresholdType::Range => {
+                    let upper = secondary_threshold.unwrap_or(*threshold_value);
+                    value < *threshold_value || value > upper
+                }
+                AlertThresholdType::Change
+                | AlertThresholdType::Stale
+                | AlertThresholdType::AccountChange => false,
+            },
+        }
+    }
+}
+
+/// Watch on an upgradeable program's deployment
+#[account]
+#[derive(Default, InitSpace)]
+pub struct ProgramWatch {
+    /// Monitoring state this watch belongs to
+    pub monitoring_state: Pubkey,
+    /// Authority allowed to remove this watch
+    pub authority: Pubkey,
+    /// Watched program
+    pub program: Pubkey,
+    /// The program's `ProgramData` account
+    pub program_data: Pubkey,
+    /// Slot the program was last deployed at
+    pub last_deployed_slot: u64,
+    /// Current upgrade authority, `None` once the program is immutable
+    pub upgrade_authority: Option<Pubkey>,
+    /// SHA-256 hash of the first `MAX_CODE_HASH_LEN` executable bytes
+    pub code_hash: [u8; 32],
+    /// When the watch was last checked (unix timestamp)
+    pub last_checked: i64,
+    /// Number of redeployments observed since the watch was created
+    pub upgrade_count: u32,
+}
+
+impl ProgramWatch {
+    /// Stores the observed deployment and reports what changed since the
+    /// previous observation.
+    pub fn observe(
+        &mut self,
+        deployed_slot: u64,
+        upgrade_authority: Option<Pubkey>,
+        code_hash: [u8; 32],
+        now: i64,
+    ) -> ProgramChange {
+        let change = ProgramChange {
+            redeployed: deployed_slot != self.last_deployed_slot || code_hash != self.code_hash,
+            authority_changed: upgrade_authority != self.upgrade_authority,
+        };
+
+        if change.redeployed {
+            self.upgrade_count = self.upgrade_count.saturating_add(1);
+        }
+        self.last_deployed_slot = deployed_slot;
+        self.upgrade_authority = upgrade_authority;
+        self.code_hash = code_hash;
+        self.last_checked = now;
+        change
+    }
+}
+
+/// Differences found when checking a program watch
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProgramChange {
+    pub redeployed: bool,
+    pub authority_changed: bool,
+}
+
+/// Watch on an arbitrary account that is expected not to change
+#[account]
+#[derive(Default, InitSpace)]
+pub struct AccountWatch {
+    /// Monitoring state this watch belongs to
+    pub monitoring_state: Pubkey,
+    /// Authority allowed to remove this watch
+    pub authority: Pubkey,
+    /// Watched account
+    pub target: Pubkey,
+    /// Part of the account data that is hashed, or all of it if `None`
+    pub range: Option<DataRange>,
+    /// `AccountChange` alert fired when the account changes
+    pub alert: Pubkey,
+    /// SHA-256 hash of the watched data in the baseline
+    pub data_hash: [u8; 32],
+    /// Owner of the account in the baseline
+    pub owner: Pubkey,
+    /// Lamports of the account in the baseline
+    pub lamports: u64,
+    /// When the watch was last checked (unix timestamp)
+    pub last_checked: i64,
+    /// Number of checks that found the account differing from the baseline
+    pub change_count: u32,
+}
+
+impl AccountWatch {
+    /// Reports what differs between the observed account state and the
+    /// baseline. The baseline only moves when the authority resets it, so
+    /// a change keeps being reported until then.
+    pub fn observe(
+        &mut self,
+        data_hash: [u8; 32],
+        owner: Pubkey,
+        lamports: u64,
+        now: i64,
+    ) -> AccountDiff {
+        let diff = AccountDiff {
+            data_changed: data_hash != self.data_hash,
+            owner_changed: owner != self.owner,
+            lamports_changed: lamports != self.lamports,
+        };
+
+        if diff.any() {
+            self.change_count = self.change_count.saturating_add(1);
+        }
+        self.last_checked = now;
+        diff
+    }
+
+    /// Accepts the observed account state as the new baseline.
+    pub fn set_baseline(&mut self, data_hash: [u8; 32], owner: Pubkey, lamports: u64, now: i64) {
+        self.data_hash = data_hash;
+        self.owner = owner;
+        self.lamports = lamports;
+        self.last_checked = now;
+    }
+}
+
+/// Byte range of an account's data covered by an account watch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, InitSpace)]
+pub struct DataRange {
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl DataRange {
+    /// The covered bytes, clamped to the data actually present so that a
+    /// shrinking account still registers as a change.
+    pub fn slice<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        let start = (self.offset as usize).min(data.len());
+        let end = start.saturating_add(self.length as usize).min(data.len());
+        &data[start..end]
+    }
+
+    pub fn fits(&self, data_len: usize) -> bool {
+        self.length > 0
+            && (self.offset as usize)
+                .checked_add(self.length as usize)
+                .is_some_and(|end| end <= data_len)
+    }
+}
+
+/// Differences found when checking an account watch
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccountDiff {
+    pub data_changed: bool,
+    pub owner_changed: bool,
+    pub lamports_changed: bool,
+}
+
+impl AccountDiff {
+    pub fn any(&self) -> bool {
+        self.data_changed || self.owner_changed || self.lamports_changed
+    }
+}
+
+/// Parameters configuring an alert
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct AlertConfigParams {
+    /// Threshold type (above/below/change)
+    pub threshold_type: AlertThresholdType,
+    /// Threshold value that triggers the alert
+    pub threshold_value: i64,
+    /// Optional secondary threshold for range-based alerts
+    pub secondary_threshold: Option<i64>,
+    /// Minimum time between alert triggers (seconds)
+    pub min_trigger_interval: u32,
+    /// Number of consecutive violations before triggering
+    pub required_violations: u8,
+    /// Number of consecutive recovered values before a firing alert resolves
+    pub recovery_count: u8,
+    /// Distance a value must clear the threshold by to count as recovered
+    pub hysteresis: i64,
+    /// Optional webhook URL for notifications
+    #[max_len(MAX_WEBHOOK_URL_LENGTH)]
+    pub webhook_url: Option<String>,
+}
+
+/// Types of alert thresholds
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, InitSpace)]
+pub enum AlertThresholdType {
+    #[default]
+    Above,
+    Below,
+    Change,
+    Range,
+    /// No update for more than `threshold_value` seconds, checked via
+    /// `check_staleness`
+    Stale,
+    /// The watched account's data hash, owner or lamports changed, checked
+    /// via `check_account_watch`
+    AccountChange,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(kind: MetricKind) -> MetricAccount {
+        MetricAccount {
+            enabled: true,
+            kind_state: MetricKindState::new(&kind),
+            params: MetricParams {
+                kind,
+                store_history: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn alert(threshold_type: AlertThresholdType, threshold_value: i64) -> AlertConfig {
+        let params = AlertConfigParams {
+            threshold_type,
+            threshold_value,
+            required_violations: 1,
+            recovery_count: 1,
+            ..Default::default()
+        };
+        AlertConfig::new(Pubkey::default(), Pubkey::default(), Pubkey::default(), 0, params)
+    }
+
+    #[test]
+    fn counter_rate_is_fixed_point() {
+        let mut metric = metric(MetricKind::Counter);
+        metric.record(100, 10, MAX_DATA_POINTS).unwrap();
+        // One unit every 60 seconds would truncate to zero as an integer
+        metric.record(160, 11, MAX_DATA_POINTS).unwrap();
+
+        let MetricKindState::Counter { rate_per_second, resets, .. } = metric.kind_state else {
+            panic!("expected counter state");
+        };
+        assert_eq!(rate_per_second, 16_666);
+        assert_eq!(resets, 0);
+    }
+
+    #[test]
+    fn counter_reset_counts_from_zero() {
+        let mut metric = metric(MetricKind::Counter);
+        metric.record(100, 50, MAX_DATA_POINTS).unwrap();
+        metric.record(110, 20, MAX_DATA_POINTS).unwrap();
+
+        let MetricKindState::Counter { rate_per_second, resets, last_value } = metric.kind_state
+        else {
+            panic!("expected counter state");
+        };
+        assert_eq!(resets, 1);
+        assert_eq!(last_value, Some(20));
+        assert_eq!(rate_per_second, 2_000_000);
+    }
+
+    #[test]
+    fn counter_rejects_negative_values() {
+        let mut metric = metric(MetricKind::Counter);
+        assert_eq!(
+            metric.record(100, -1, MAX_DATA_POINTS).unwrap_err(),
+            MonitoringError::NegativeCounterValue.into()
+        );
+    }
+
+    #[test]
+    fn histogram_buckets_by_upper_bound() {
+        let mut metric = metric(MetricKind::Histogram {
+            bucket_bounds: vec![10, 100],
+        });
+        for (timestamp, value) in [(1, 5), (2, 10), (3, 11), (4, 100), (5, 1_000)] {
+            metric.record(timestamp, value, MAX_DATA_POINTS).unwrap();
+        }
+
+        let MetricKindState::Histogram { bucket_counts, sum, count } = &metric.kind_state else {
+            panic!("expected histogram state");
+        };
+        assert_eq!(bucket_counts, &vec![2, 2, 1]);
+        assert_eq!(*sum, 1_126);
+        assert_eq!(*count, 5);
+    }
+
+    #[test]
+    fn failed_record_leaves_metric_untouched() {
+        let mut metric = metric(MetricKind::Counter);
+        metric.params.max_pages = 1;
+        let mut page = MetricPage {
+            points: (0..MAX_PAGE_POINTS as i64)
+                .map(|timestamp| MetricDataPoint { timestamp, value: timestamp })
+                .collect(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            metric.record_to_page(&mut page, 1_000, 1_000).unwrap_err(),
+            MonitoringError::MetricPageFull.into()
+        );
+        assert_eq!(metric.last_updated, 0);
+        assert_eq!(metric.aggregates.count, 0);
+        assert_eq!(metric.kind_state, MetricKindState::new(&MetricKind::Counter));
+    }
+
+    #[test]
+    fn composite_cannot_start_firing_while_silenced() {
+        let mut composite = CompositeAlert {
+            enabled: true,
+            snoozed_until: 100,
+            ..Default::default()
+        };
+
+        assert_eq!(composite.advance(true, 50, false), AlertTransition::Unchanged);
+        assert_eq!(composite.advance(true, 150, true), AlertTransition::Unchanged);
+        assert_eq!(composite.state, AlertState::Ok);
+
+        assert_eq!(composite.advance(true, 150, false), AlertTransition::Triggered);
+        // A firing composite alert still resolves while suppressed
+        assert_eq!(composite.advance(false, 160, true), AlertTransition::Resolved);
+    }
+
+    /// Appends `value` every 10 seconds from `timestamp` until `page` stops
+    /// taking new points. Returns the timestamp after the last one.
+    fn fill_page(
+        metric: &mut MetricAccount,
+        page: &mut MetricPage,
+        mut timestamp: i64,
+        value: i64,
+    ) -> i64 {
+        while metric.write_page_index() == page.page_index {
+            metric.record_to_page(page, timestamp, value).unwrap();
+            timestamp += 10;
+        }
+        timestamp
+    }
+
+    #[test]
+    fn paged_window_spans_open_pages() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.max_pages = 1;
+        let mut first = MetricPage::default();
+        let mut second = MetricPage {
+            page_index: 1,
+            ..Default::default()
+        };
+
+        let timestamp = fill_page(&mut metric, &mut first, 1, 50);
+        metric.record_to_page(&mut second, timestamp, 10).unwrap();
+        assert_eq!((metric.aggregates.window_min, metric.aggregates.window_max), (10, 50));
+
+        // Only one page beyond `max_pages` may be open
+        let mut third = MetricPage {
+            page_index: 2,
+            ..Default::default()
+        };
+        let timestamp = fill_page(&mut metric, &mut second, timestamp + 10, 10);
+        assert_eq!(
+            metric.record_to_page(&mut third, timestamp, 20).unwrap_err(),
+            MonitoringError::MetricPageNotClosed.into()
+        );
+
+        assert_eq!(metric.close_oldest_page(&first), 0);
+        assert_eq!((metric.aggregates.window_min, metric.aggregates.window_max), (10, 10));
+        metric.record_to_page(&mut third, timestamp, 20).unwrap();
+        assert_eq!((metric.aggregates.window_min, metric.aggregates.window_max), (10, 20));
+    }
+
+    #[test]
+    fn rollup_folds_points_into_buckets() {
+        let mut rollup = MetricRollup::default();
+        rollup.fold(60, 5, 2).unwrap();
+        rollup.fold(119, 9, 2).unwrap();
+        // Late point within the same bucket keeps the latest value
+        rollup.fold(61, 1, 2).unwrap();
+
+        assert_eq!(rollup.buckets.len(), 1);
+        let bucket = rollup.buckets[0];
+        assert_eq!(bucket.start, 60);
+        assert_eq!(bucket.count, 3);
+        assert_eq!(bucket.sum, 15);
+        assert_eq!((bucket.min, bucket.max), (1, 9));
+        assert_eq!((bucket.last, bucket.last_at), (9, 119));
+    }
+
+    #[test]
+    fn rollup_drops_buckets_beyond_retention() {
+        let mut rollup = MetricRollup::default();
+        for minute in [1, 2, 3] {
+            rollup.fold(minute * 60, minute, 2).unwrap();
+        }
+        let starts: Vec<i64> = rollup.buckets.iter().map(|bucket| bucket.start).collect();
+        assert_eq!(starts, vec![120, 180]);
+
+        // Older than every retained bucket of a full tier
+        rollup.fold(30, 100, 2).unwrap();
+        let starts: Vec<i64> = rollup.buckets.iter().map(|bucket| bucket.start).collect();
+        assert_eq!(starts, vec![120, 180]);
+    }
+
+    #[test]
+    fn prune_drops_expired_inline_points() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.ttl_seconds = 100;
+        for (timestamp, value) in [(10, 90), (50, 5), (120, 20), (150, 30)] {
+            metric.record(timestamp, value, MAX_DATA_POINTS).unwrap();
+        }
+
+        assert_eq!(metric.prune(200), 2);
+        let retained: Vec<i64> = metric.data_points.iter().map(|point| point.timestamp).collect();
+        assert_eq!(retained, vec![120, 150]);
+        assert_eq!((metric.aggregates.window_min, metric.aggregates.window_max), (20, 30));
+        assert_eq!(metric.prune(200), 0);
+    }
+
+    #[test]
+    fn prune_drops_expired_history_points() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.ttl_seconds = 100;
+        let mut history = Box::new(MetricHistory::zeroed());
+        for (timestamp, value) in [(10, 90), (50, 5), (120, 20), (150, 30)] {
+            metric.record_to_history(&mut history, timestamp, value).unwrap();
+        }
+
+        assert_eq!(metric.prune_history(&mut history, 200), 2);
+        let retained: Vec<i64> = history.iter().map(|point| point.timestamp).collect();
+        assert_eq!(retained, vec![120, 150]);
+        assert_eq!(history.window(), Some((20, 30)));
+        assert_eq!((metric.aggregates.window_min, metric.aggregates.window_max), (20, 30));
+    }
+
+    #[test]
+    fn only_fully_expired_closed_pages_expire() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.ttl_seconds = 100;
+        metric.current_page = 1;
+        let page = |page_index, timestamps: &[i64]| MetricPage {
+            page_index,
+            points: timestamps
+                .iter()
+                .map(|&timestamp| MetricDataPoint { timestamp, value: 0 })
+                .collect(),
+            ..Default::default()
+        };
+
+        assert!(metric.page_expired(&page(0, &[10, 50]), 200));
+        assert!(!metric.page_expired(&page(0, &[10, 150]), 200));
+        // The page being written is kept
+        assert!(!metric.page_expired(&page(1, &[10, 50]), 200));
+    }
+
+    #[test]
+    fn late_points_are_inserted_in_order_once() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.late_arrival_window = 100;
+        metric.record(10, 1, MAX_DATA_POINTS).unwrap();
+        metric.record(30, 3, MAX_DATA_POINTS).unwrap();
+
+        assert_eq!(
+            metric.record(20, 2, MAX_DATA_POINTS).unwrap(),
+            RecordOutcome::Inserted { previous: Some(1) }
+        );
+        assert_eq!(metric.record(20, 9, MAX_DATA_POINTS).unwrap(), RecordOutcome::Duplicate);
+        assert_eq!(metric.record(30, 9, MAX_DATA_POINTS).unwrap(), RecordOutcome::Duplicate);
+        let timestamps: Vec<_> = metric.data_points.iter().map(|point| point.timestamp).collect();
+        assert_eq!(timestamps, vec![10, 20, 30]);
+        assert_eq!((metric.last_updated, metric.aggregates.last), (30, 3));
+        assert_eq!(metric.aggregates.count, 3);
+
+        metric.record(200, 4, MAX_DATA_POINTS).unwrap();
+        assert_eq!(
+            metric.record(50, 5, MAX_DATA_POINTS).unwrap_err(),
+            MonitoringError::LateArrivalOutsideWindow.into()
+        );
+    }
+
+    #[test]
+    fn first_point_at_the_creation_timestamp_is_recorded() {
+        let mut inline = metric(MetricKind::Gauge);
+        inline.last_updated = 100;
+        assert_eq!(inline.record(100, 5, MAX_DATA_POINTS).unwrap(), RecordOutcome::Appended);
+        assert_eq!((inline.aggregates.count, inline.data_points.len()), (1, 1));
+        assert_eq!(inline.record(100, 6, MAX_DATA_POINTS).unwrap(), RecordOutcome::Duplicate);
+
+        let mut paged = metric(MetricKind::Gauge);
+        paged.params.max_pages = 1;
+        paged.last_updated = 100;
+        let mut page = MetricPage::default();
+        assert_eq!(paged.record_to_page(&mut page, 100, 5).unwrap(), RecordOutcome::Appended);
+        assert_eq!(paged.existing_pages(), 1);
+
+        let mut zero_copy = metric(MetricKind::Gauge);
+        zero_copy.last_updated = 100;
+        let mut history = MetricHistory::zeroed();
+        assert_eq!(
+            zero_copy.record_to_history(&mut history, 100, 5).unwrap(),
+            RecordOutcome::Appended
+        );
+        assert_eq!(history.len, 1);
+    }
+
+    #[test]
+    fn duplicates_are_detected_before_validation() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.max_value = Some(10);
+        metric.record(10, 5, MAX_DATA_POINTS).unwrap();
+
+        // Outside the late-arrival window and above the maximum, but a replay
+        assert_eq!(metric.record(10, 50, MAX_DATA_POINTS).unwrap(), RecordOutcome::Duplicate);
+    }
+
+    #[test]
+    fn late_points_must_follow_the_history_floor() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.late_arrival_window = 100;
+        metric.params.retention_period = 2;
+        for timestamp in [10, 20, 30] {
+            metric.record(timestamp, 1, MAX_DATA_POINTS).unwrap();
+        }
+        assert_eq!(metric.history_floor, 10);
+
+        // The evicted point can no longer be deduplicated
+        assert_eq!(
+            metric.record(10, 1, MAX_DATA_POINTS).unwrap_err(),
+            MonitoringError::LatePointNotRetained.into()
+        );
+        // Older than everything retained, so counted but not stored
+        assert_eq!(
+            metric.record(15, 1, MAX_DATA_POINTS).unwrap(),
+            RecordOutcome::Inserted { previous: None }
+        );
+        assert_eq!(metric.history_floor, 15);
+        assert_eq!(
+            metric.record(15, 1, MAX_DATA_POINTS).unwrap_err(),
+            MonitoringError::LatePointNotRetained.into()
+        );
+        assert_eq!(metric.aggregates.count, 4);
+    }
+
+    #[test]
+    fn late_points_are_checked_against_the_zero_copy_history() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.late_arrival_window = 100;
+        metric.params.retention_period = 2;
+        let mut history = MetricHistory::zeroed();
+        for timestamp in [10, 20, 30] {
+            metric.record_to_history(&mut history, timestamp, timestamp).unwrap();
+        }
+
+        assert_eq!(
+            metric.record_to_history(&mut history, 25, 25).unwrap(),
+            RecordOutcome::Inserted { previous: Some(20) }
+        );
+        assert_eq!(
+            metric.record_to_history(&mut history, 25, 25).unwrap(),
+            RecordOutcome::Duplicate
+        );
+        assert_eq!(metric.history_floor, 20);
+        assert_eq!(
+            metric.record_to_history(&mut history, 20, 20).unwrap_err(),
+            MonitoringError::LatePointNotRetained.into()
+        );
+    }
+
+    #[test]
+    fn late_points_are_routed_to_the_page_covering_them() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.max_pages = 2;
+        metric.params.late_arrival_window = 10_000;
+        let mut first = MetricPage::default();
+        let mut second = MetricPage {
+            page_index: 1,
+            ..Default::default()
+        };
+        let timestamp = fill_page(&mut metric, &mut first, 10, 1);
+        let last_of_first = timestamp - 10;
+        metric.record_to_page(&mut second, timestamp, 2).unwrap();
+
+        assert_eq!(metric.page_index_for(timestamp + 10), 1);
+        assert_eq!(metric.page_index_for(timestamp - 5), 0);
+        assert_eq!(metric.page_index_for(5), 0);
+        assert_eq!(
+            metric.record_to_page(&mut second, 15, 3).unwrap_err(),
+            MonitoringError::MetricPageMismatch.into()
+        );
+        assert_eq!(
+            metric.record_to_page(&mut first, 15, 3).unwrap(),
+            RecordOutcome::Inserted { previous: Some(1) }
+        );
+        assert_eq!(metric.record_to_page(&mut first, 20, 3).unwrap(), RecordOutcome::Duplicate);
+
+        // The slots kept for late points fill up the page
+        for slot in 1..PAGE_LATE_SLOTS as i64 {
+            metric.record_to_page(&mut first, 15 + slot * 10, 3).unwrap();
+        }
+        assert_eq!(first.points.len(), MAX_PAGE_POINTS);
+        assert_eq!(
+            metric.record_to_page(&mut first, 15 + PAGE_LATE_SLOTS as i64 * 10, 3).unwrap_err(),
+            MonitoringError::MetricPageFull.into()
+        );
+        assert_eq!(metric.current_page_len, 1);
+
+        assert_eq!(metric.close_oldest_page(&first), 0);
+        assert_eq!(metric.history_floor, last_of_first);
+    }
+
+    #[test]
+    fn late_violations_count_but_late_recoveries_do_not() {
+        let mut alert = alert(AlertThresholdType::Above, 50);
+        alert.params.required_violations = 2;
+
+        assert_eq!(alert.evaluate(60, None, 100, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate_late(70, None, 101, false), AlertTransition::Triggered);
+        // Only the latest value may resolve the alert
+        assert_eq!(alert.evaluate_late(10, None, 102, false), AlertTransition::Unchanged);
+        assert_eq!(alert.state, AlertState::Firing);
+        assert_eq!(alert.evaluate(10, None, 103, false), AlertTransition::Resolved);
+    }
+
+    fn timestamps(history: &MetricHistory) -> Vec<i64> {
+        history.iter().map(|point| point.timestamp).collect()
+    }
+
+    #[test]
+    fn history_evicts_the_oldest_points_once_full() {
+        let mut history = MetricHistory::zeroed();
+        for timestamp in 1..=3 {
+            assert!(history.push(MetricDataPoint { timestamp, value: timestamp * 10 }, 3).is_empty());
+        }
+
+        let evicted = history.push(MetricDataPoint { timestamp: 4, value: 5 }, 3);
+        assert_eq!(evicted, vec![MetricDataPoint { timestamp: 1, value: 10 }]);
+        assert_eq!(timestamps(&history), vec![2, 3, 4]);
+        assert_eq!(history.window(), Some((5, 30)));
+        assert!(!history.contains(1));
+        assert!(history.contains(4));
+
+        // Shrinking the capacity evicts every point beyond it
+        let evicted = history.push(MetricDataPoint { timestamp: 5, value: 50 }, 2);
+        assert_eq!(evicted.len(), 2);
+        assert_eq!(timestamps(&history), vec![4, 5]);
+        assert_eq!(history.window(), Some((5, 50)));
+    }
+
+    #[test]
+    fn history_wraps_around_the_ring_buffer() {
+        let mut history = MetricHistory::zeroed();
+        let capacity = MetricHistory::CAPACITY;
+        for timestamp in 0..i64::from(capacity) + 3 {
+            history.push(MetricDataPoint { timestamp, value: timestamp }, capacity);
+        }
+
+        assert_eq!(history.len, capacity);
+        assert_eq!(history.get(0).map(|point| point.timestamp), Some(3));
+        assert_eq!(history.latest().map(|point| point.timestamp), Some(i64::from(capacity) + 2));
+        assert_eq!(history.window(), Some((3, i64::from(capacity) + 2)));
+        assert!(!history.contains(2));
+        assert!(history.contains(i64::from(capacity) + 2));
+    }
+
+    #[test]
+    fn history_insert_keeps_timestamp_order() {
+        let mut history = MetricHistory::zeroed();
+        for timestamp in [10, 20, 40] {
+            history.push(MetricDataPoint { timestamp, value: timestamp }, 4);
+        }
+
+        assert!(history.insert(MetricDataPoint { timestamp: 30, value: 1 }, 4).is_empty());
+        assert_eq!(timestamps(&history), vec![10, 20, 30, 40]);
+        assert_eq!(history.window(), Some((1, 40)));
+        assert_eq!(history.position(25), 2);
+
+        // Full: the oldest point makes room for a late one
+        let evicted = history.insert(MetricDataPoint { timestamp: 15, value: 15 }, 4);
+        assert_eq!(evicted, vec![MetricDataPoint { timestamp: 10, value: 10 }]);
+        assert_eq!(timestamps(&history), vec![15, 20, 30, 40]);
+
+        // Older than everything retained in a full buffer, so not stored
+        let point = MetricDataPoint { timestamp: 5, value: 5 };
+        assert_eq!(history.insert(point, 4), vec![point]);
+        assert_eq!(timestamps(&history), vec![15, 20, 30, 40]);
+    }
+
+    #[test]
+    fn rate_of_change_is_bounded_per_second() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.max_rate_of_change = Some(2);
+        metric.params.late_arrival_window = 10;
+        metric.record(10, 100, MAX_DATA_POINTS).unwrap();
+
+        metric.validate_value(110, 15).unwrap();
+        metric.validate_value(90, 15).unwrap();
+        assert_eq!(
+            metric.validate_value(111, 15).unwrap_err(),
+            MonitoringError::RateOfChangeExceeded.into()
+        );
+        // Late points are not compared against the latest one
+        metric.validate_value(1_000, 5).unwrap();
+    }
+
+    #[test]
+    fn counter_reset_is_rate_checked_from_zero() {
+        let mut metric = metric(MetricKind::Counter);
+        metric.params.max_rate_of_change = Some(1);
+        metric.record(10, 100, MAX_DATA_POINTS).unwrap();
+
+        metric.validate_value(5, 15).unwrap();
+        assert_eq!(
+            metric.validate_value(6, 15).unwrap_err(),
+            MonitoringError::RateOfChangeExceeded.into()
+        );
+    }
+
+    #[test]
+    fn late_points_must_fall_within_the_window() {
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.late_arrival_window = 10;
+        // Nothing recorded yet to be late against
+        metric.last_updated = 50;
+        assert_eq!(
+            metric.validate_value(1, 49).unwrap_err(),
+            MonitoringError::InvalidTimestamp.into()
+        );
+
+        metric.record(100, 1, MAX_DATA_POINTS).unwrap();
+        metric.validate_value(1, 90).unwrap();
+        assert_eq!(
+            metric.validate_value(1, 89).unwrap_err(),
+            MonitoringError::LateArrivalOutsideWindow.into()
+        );
+        assert_eq!(
+            metric.validate_value(1, i64::MIN).unwrap_err(),
+            MonitoringError::LateArrivalOutsideWindow.into()
+        );
+    }
+
+    #[test]
+    fn alert_fires_after_required_violations() {
+        let mut alert = alert(AlertThresholdType::Above, 50);
+        alert.params.required_violations = 3;
+
+        assert_eq!(alert.evaluate(60, None, 100, false), AlertTransition::Unchanged);
+        assert_eq!(alert.state, AlertState::Pending);
+        // A value within the threshold restarts the count
+        assert_eq!(alert.evaluate(40, None, 101, false), AlertTransition::Unchanged);
+        assert_eq!(alert.state, AlertState::Ok);
+
+        assert_eq!(alert.evaluate(60, None, 102, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate(60, None, 103, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate(60, None, 104, false), AlertTransition::Triggered);
+        assert_eq!((alert.state, alert.firing_since), (AlertState::Firing, 104));
+        assert_eq!(alert.evaluate(60, None, 105, false), AlertTransition::Unchanged);
+    }
+
+    #[test]
+    fn alert_waits_for_its_cooldown() {
+        let mut alert = alert(AlertThresholdType::Above, 50);
+        alert.params.min_trigger_interval = 60;
+
+        assert_eq!(alert.evaluate(60, None, 100, false), AlertTransition::Triggered);
+        assert_eq!(alert.evaluate(10, None, 110, false), AlertTransition::Resolved);
+        assert_eq!(alert.evaluate(60, None, 120, false), AlertTransition::Unchanged);
+        assert_eq!(alert.state, AlertState::Pending);
+        assert_eq!(alert.evaluate(60, None, 160, false), AlertTransition::Triggered);
+    }
+
+    #[test]
+    fn alert_resolves_outside_the_hysteresis_band() {
+        let mut alert = alert(AlertThresholdType::Above, 50);
+        alert.params.hysteresis = 10;
+        alert.params.recovery_count = 2;
+        assert_eq!(alert.evaluate(60, None, 100, false), AlertTransition::Triggered);
+
+        // Back under the threshold but inside the band
+        assert_eq!(alert.evaluate(45, None, 101, false), AlertTransition::Unchanged);
+        assert_eq!(alert.consecutive_recoveries, 0);
+        assert_eq!(alert.evaluate(40, None, 102, false), AlertTransition::Unchanged);
+        assert_eq!(alert.consecutive_recoveries, 1);
+        // Re-entering the band restarts the recovery count
+        assert_eq!(alert.evaluate(45, None, 103, false), AlertTransition::Unchanged);
+        assert_eq!(alert.consecutive_recoveries, 0);
+
+        assert_eq!(alert.evaluate(40, None, 104, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate(30, None, 105, false), AlertTransition::Resolved);
+        assert_eq!((alert.state, alert.resolved_at), (AlertState::Resolved, 105));
+    }
+
+    #[test]
+    fn suppressed_or_disabled_alerts_do_not_fire() {
+        let mut alert = alert(AlertThresholdType::Above, 50);
+        assert_eq!(alert.evaluate(60, None, 100, true), AlertTransition::Unchanged);
+        assert_eq!(alert.state, AlertState::Pending);
+
+        alert.snoozed_until = 200;
+        assert_eq!(alert.evaluate(60, None, 150, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate(60, None, 200, false), AlertTransition::Triggered);
+
+        alert.enabled = false;
+        assert_eq!(alert.evaluate(10, None, 210, false), AlertTransition::Unchanged);
+        assert_eq!(alert.state, AlertState::Firing);
+    }
+
+    #[test]
+    fn resetting_a_firing_alert_resolves_it() {
+        let mut alert = alert(AlertThresholdType::Above, 50);
+        assert_eq!(alert.evaluate(60, None, 100, false), AlertTransition::Triggered);
+        assert_eq!(alert.reset(110), AlertTransition::Resolved);
+        assert_eq!((alert.state, alert.resolved_at), (AlertState::Resolved, 110));
+
+        assert_eq!(alert.evaluate(60, None, 120, false), AlertTransition::Triggered);
+        assert_eq!(alert.evaluate(10, None, 130, false), AlertTransition::Resolved);
+        assert_eq!(alert.reset(140), AlertTransition::Unchanged);
+        assert_eq!((alert.state, alert.resolved_at), (AlertState::Ok, 130));
+    }
+
+    #[test]
+    fn change_alert_compares_against_the_previous_value() {
+        let mut alert = alert(AlertThresholdType::Change, 10);
+        assert_eq!(alert.evaluate(100, None, 100, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate(105, Some(100), 101, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate(90, Some(105), 102, false), AlertTransition::Triggered);
+        assert_eq!(alert.evaluate(95, Some(90), 103, false), AlertTransition::Resolved);
+    }
+
+    #[test]
+    fn late_points_are_rate_limited_by_cluster_time() {
+        use crate::instructions::validate_update;
+
+        let config = MonitoringConfig {
+            min_update_interval: 60,
+            rate_limiting_enabled: true,
+            ..Default::default()
+        };
+        let mut metric = metric(MetricKind::Gauge);
+        metric.params.late_arrival_window = 1_000;
+        metric.record(1_000, 1, MAX_DATA_POINTS).unwrap();
+        metric.late_recorded_at = 5_000;
+
+        validate_update(&config, &metric, 1_060, 5_000).unwrap();
+        assert_eq!(
+            validate_update(&config, &metric, 1_059, 5_000).unwrap_err(),
+            MonitoringError::UpdateTooFrequent.into()
+        );
+        assert_eq!(
+            validate_update(&config, &metric, 900, 5_059).unwrap_err(),
+            MonitoringError::UpdateTooFrequent.into()
+        );
+        validate_update(&config, &metric, 900, 5_060).unwrap();
+    }
+
+    #[test]
+    fn sourced_metrics_cannot_be_paged() {
+        use crate::instructions::validate_metric_params;
+
+        let config = MonitoringConfig::default();
+        let mut params = MetricParams {
+            store_history: true,
+            max_pages: 1,
+            ..Default::default()
+        };
+        validate_metric_params(&config, &params).unwrap();
+
+        params.source = Some(MetricSource {
+            account: Pubkey::new_unique(),
+            field: SourceField::Lamports,
+            sample_interval: MIN_SAMPLE_INTERVAL,
+        });
+        assert_eq!(
+            validate_metric_params(&config, &params).unwrap_err(),
+            MonitoringError::InvalidMetricParams.into()
+        );
+    }
+
+    #[test]
+    fn account_watch_keeps_its_baseline_until_reset() {
+        let owner = Pubkey::new_unique();
+        let mut watch = AccountWatch {
+            data_hash: [1; 32],
+            owner,
+            lamports: 100,
+            ..Default::default()
+        };
+
+        assert!(!watch.observe([1; 32], owner, 100, 10).any());
+        let diff = watch.observe([2; 32], owner, 100, 20);
+        assert!(diff.data_changed && !diff.owner_changed && !diff.lamports_changed);
+        // Still reported on the next check
+        let diff = watch.observe([2; 32], Pubkey::new_unique(), 50, 30);
+        assert!(diff.data_changed && diff.owner_changed && diff.lamports_changed);
+        assert_eq!((watch.change_count, watch.last_checked), (2, 30));
+        assert_eq!(watch.data_hash, [1; 32]);
+
+        watch.set_baseline([2; 32], owner, 100, 40);
+        assert!(!watch.observe([2; 32], owner, 100, 50).any());
+    }
+
+    #[test]
+    fn account_change_alert_fires_on_first_change_and_holds() {
+        let mut alert = alert(AlertThresholdType::AccountChange, 0);
+        alert.params.required_violations = 3;
+
+        assert_eq!(alert.evaluate_account_change(false, 10, false), AlertTransition::Unchanged);
+        assert_eq!(alert.evaluate_account_change(true, 20, false), AlertTransition::Triggered);
+        // Only resetting the baseline resolves it
+        assert_eq!(alert.evaluate_account_change(false, 30, false), AlertTransition::Unchanged);
+        assert_eq!(alert.state, AlertState::Firing);
+        assert_eq!(alert.reset(40), AlertTransition::Resolved);
+    }
+
+    #[test]
+    fn stale_alert_counts_elapsed_periods_not_checks() {
+        let mut alert = alert(AlertThresholdType::Stale, 60);
+        alert.params.required_violations = 2;
+
+        for _ in 0..5 {
+            assert_eq!(alert.evaluate_staleness(0, 61, false), AlertTransition::Unchanged);
+        }
+        assert_eq!((alert.state, alert.consecutive_violations), (AlertState::Pending, 1));
+        assert_eq!(alert.evaluate_staleness(0, 120, false), AlertTransition::Triggered);
+        assert_eq!(alert.evaluate_staleness(100, 120, false), AlertTransition::Resolved);
+    }
+
+    #[test]
+    fn counter_rate_overflow_is_an_error() {
+        let mut metric = metric(MetricKind::Counter);
+        metric.record(1, 0, MAX_DATA_POINTS).unwrap();
+        assert_eq!(
+            metric.record(2, i64::MAX, MAX_DATA_POINTS).unwrap_err(),
+            MonitoringError::ArithmeticOverflow.into()
+        );
+        assert_eq!(metric.aggregates.count, 1);
+    }
+
+    fn read_integer(
+        data: &mut [u8],
+        offset: u32,
+        width: u8,
+        signed: bool,
+        skip_discriminator: bool,
+    ) -> Result<i64> {
+        let key = Pubkey::default();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, data, &key, false, 0);
+        let source = MetricSource {
+            account: key,
+            field: SourceField::Integer {
+                offset,
+                width,
+                signed,
+                skip_discriminator,
+            },
+            sample_interval: MIN_SAMPLE_INTERVAL,
+        };
+        source.read(&info)
+    }
+
+    #[test]
+    fn source_reads_little_endian_integer_at_offset() {
+        let mut data = [0u8; 16];
+        data[10..12].copy_from_slice(&0x1234u16.to_le_bytes());
+
+        assert_eq!(read_integer(&mut data, 10, 2, false, false).unwrap(), 0x1234);
+        // The offset is counted after the discriminator
+        assert_eq!(read_integer(&mut data, 2, 2, false, true).unwrap(), 0x1234);
+    }
+
+    #[test]
+    fn source_sign_extends_signed_integers() {
+        let mut data = [0xffu8, 0x7f, 0xfe, 0xff, 0xff, 0xff];
+
+        assert_eq!(read_integer(&mut data, 0, 1, true, false).unwrap(), -1);
+        assert_eq!(read_integer(&mut data, 0, 1, false, false).unwrap(), 255);
+        assert_eq!(read_integer(&mut data, 0, 2, true, false).unwrap(), 0x7fff);
+        assert_eq!(read_integer(&mut data, 2, 4, true, false).unwrap(), -2);
+    }
+
+    #[test]
+    fn source_rejects_out_of_bounds_and_overflowing_reads() {
+        let mut data = [0xffu8; 8];
+
+        assert_eq!(
+            read_integer(&mut data, 6, 4, false, false).unwrap_err(),
+            MonitoringError::SourceOutOfBounds.into()
+        );
+        assert_eq!(
+            read_integer(&mut data, 0, 1, false, true).unwrap_err(),
+            MonitoringError::SourceOutOfBounds.into()
+        );
+        assert_eq!(
+            read_integer(&mut data, 0, 8, false, false).unwrap_err(),
+            MonitoringError::SourceValueOverflow.into()
+        );
+        assert_eq!(read_integer(&mut data, 0, 8, true, false).unwrap(), -1);
+    }
+
+    #[test]
+    fn program_watch_counts_redeployments() {
+        let upgrade_authority = Some(Pubkey::new_unique());
+        let mut watch = ProgramWatch {
+            last_deployed_slot: 10,
+            upgrade_authority,
+            code_hash: [1; 32],
+            ..Default::default()
+        };
+
+        let change = watch.observe(10, upgrade_authority, [1; 32], 100);
+        assert_eq!(change, ProgramChange::default());
+        assert_eq!((watch.upgrade_count, watch.last_checked), (0, 100));
+
+        // A new code hash counts even if the slot is unchanged
+        let change = watch.observe(10, upgrade_authority, [2; 32], 110);
+        assert!(change.redeployed && !change.authority_changed);
+        let change = watch.observe(20, None, [2; 32], 120);
+        assert!(change.redeployed && change.authority_changed);
+        assert_eq!(watch.upgrade_count, 2);
+        assert_eq!((watch.last_deployed_slot, watch.upgrade_authority), (20, None));
+
+        let change = watch.observe(20, None, [2; 32], 130);
+        assert_eq!(change, ProgramChange::default());
+    }
+
+    #[test]
+    fn data_range_fits_and_clamps_to_the_data() {
+        let range = DataRange { offset: 4, length: 4 };
+        assert!(range.fits(8));
+        assert!(!range.fits(7));
+        assert!(!DataRange { offset: 0, length: 0 }.fits(8));
+        assert!(!DataRange { offset: 8, length: 1 }.fits(8));
+
+        let data = [0u8, 1, 2, 3, 4, 5, 6, 7];
+        assert_eq!(range.slice(&data), &[4, 5, 6, 7]);
+        // A shrunk account hashes only what is left of the range
+        assert_eq!(range.slice(&data[..6]), &[4, 5]);
+        assert!(range.slice(&data[..2]).is_empty());
+    }
+
+    #[test]
+    fn maintenance_windows_cover_their_metric_until_the_end() {
+        let metric = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let global = MaintenanceWindow { id: 0, start: 100, end: 200, metric: None };
+        let scoped = MaintenanceWindow { id: 1, start: 150, end: 300, metric: Some(metric) };
+
+        assert!(!global.covers(&metric, 99));
+        assert!(global.covers(&other, 100));
+        assert!(!global.covers(&metric, 200));
+        assert!(scoped.covers(&metric, 250));
+        assert!(!scoped.covers(&other, 250));
+
+        let mut state = MonitoringState {
+            maintenance_windows: vec![global, scoped],
+            ..Default::default()
+        };
+        assert!(state.in_maintenance(&other, 150));
+        state.prune_maintenance_windows(200);
+        assert_eq!(state.maintenance_windows.len(), 1);
+        assert!(!state.in_maintenance(&other, 250));
+        assert!(state.in_maintenance(&metric, 250));
+        state.prune_maintenance_windows(300);
+        assert!(state.maintenance_windows.is_empty());
+    }
+}
@@ -61,13 +61,18 @@ describe('chain-watch', () => {
             maxAlerts: 50,
         };
 
+        const namespace = new anchor.BN(0);
         const [monitoringStatePda] = PublicKey.findProgramAddressSync(
-            [Buffer.from("monitoring")],
+            [
+                Buffer.from("monitoring"),
+                authority.publicKey.toBuffer(),
+                namespace.toArrayLike(Buffer, "le", 8)
+            ],
             program.programId
         );
 
         await program.methods
-            .initializeMonitoring(monitoringConfig)
+            .initializeMonitoring(namespace, monitoringConfig)
             .accounts({
                 monitoringState: monitoringStatePda,
                 authority: authority.publicKey,
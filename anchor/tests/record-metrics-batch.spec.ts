@@ -0,0 +1,221 @@
+import { startAnchor, Clock, ProgramTestContext } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import { ChainWatch } from "../target/types/chain_watch";
+
+describe("record_metrics_batch", () => {
+    const CHAIN_WATCH_PROGRAM_ID = new PublicKey("8wq6TVhdTeWiUWXib3vxJguDE9Nm3dmV99YxFCWL3WEe");
+    const namespace = new anchor.BN(1);
+
+    let context: ProgramTestContext;
+    let program: Program<ChainWatch>;
+    let authority: Keypair;
+    let monitoringState: PublicKey;
+    let now: number;
+
+    const metricParams = (overrides: object = {}) => ({
+        minValue: null,
+        maxValue: null,
+        maxRateOfChange: null,
+        storeHistory: true,
+        retentionPeriod: 0,
+        ttlSeconds: 0,
+        kind: { gauge: {} },
+        ewmaAlphaBps: 0,
+        source: null,
+        scale: 0,
+        unit: "",
+        maxPages: 0,
+        rollups: [],
+        lateArrivalWindow: 0,
+        ...overrides,
+    });
+
+    const metricPda = (name: string) =>
+        PublicKey.findProgramAddressSync(
+            [Buffer.from("metric"), monitoringState.toBuffer(), Buffer.from(name)],
+            program.programId
+        )[0];
+
+    const alertPda = (metric: PublicKey, alertId: number) =>
+        PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("alert"),
+                monitoringState.toBuffer(),
+                metric.toBuffer(),
+                new anchor.BN(alertId).toArrayLike(Buffer, "le", 2),
+            ],
+            program.programId
+        )[0];
+
+    const writable = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+
+    const addMetric = async (name: string, overrides: object = {}) => {
+        const metric = metricPda(name);
+        await program.methods
+            .addMetric(name, metricParams(overrides))
+            .accountsPartial({
+                monitoringState,
+                metricAccount: metric,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        return metric;
+    };
+
+    // Metrics start at the creation time, so entries must be recorded after it
+    const advanceClock = async (seconds: number) => {
+        const clock = await context.banksClient.getClock();
+        now = Number(clock.unixTimestamp) + seconds;
+        context.setClock(
+            new Clock(
+                clock.slot,
+                clock.epochStartTimestamp,
+                clock.epoch,
+                clock.leaderScheduleEpoch,
+                BigInt(now)
+            )
+        );
+    };
+
+    const recordBatch = (
+        entries: { metricIndex: number; timestamp: number; value: number }[],
+        remaining: PublicKey[]
+    ) =>
+        program.methods
+            .recordMetricsBatch(
+                entries.map((entry) => ({
+                    metricIndex: entry.metricIndex,
+                    timestamp: new anchor.BN(entry.timestamp),
+                    value: new anchor.BN(entry.value),
+                }))
+            )
+            .accountsPartial({
+                monitoringState,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .remainingAccounts(remaining.map(writable))
+            .rpc();
+
+    before(async () => {
+        context = await startAnchor(
+            "",
+            [{ name: "chain_watch", programId: CHAIN_WATCH_PROGRAM_ID }],
+            []
+        );
+        const provider = new BankrunProvider(context);
+        authority = context.payer;
+        program = new Program<ChainWatch>(require("../target/idl/chain_watch.json"), provider);
+
+        [monitoringState] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("monitoring"),
+                authority.publicKey.toBuffer(),
+                namespace.toArrayLike(Buffer, "le", 8),
+            ],
+            program.programId
+        );
+
+        await program.methods
+            .initializeMonitoring(namespace, {
+                maxMetrics: 10,
+                maxAlerts: 10,
+                maxDataPoints: 100,
+                minUpdateInterval: new anchor.BN(0),
+                rateLimitingEnabled: false,
+            })
+            .accountsPartial({
+                monitoringState,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+    });
+
+    it("reports rejected entries without touching their metric", async () => {
+        const bounded = await addMetric("batch_bounded", { maxValue: new anchor.BN(100) });
+        const paged = await addMetric("batch_paged", { maxPages: 2 });
+        const createdAt = (await program.account.metricAccount.fetch(paged)).lastUpdated;
+        await advanceClock(100);
+
+        await recordBatch(
+            [
+                { metricIndex: 0, timestamp: now - 30, value: 10 },
+                // Above the metric's maximum
+                { metricIndex: 0, timestamp: now - 20, value: 500 },
+                // Paged metrics can only be recorded through record_metric
+                { metricIndex: 1, timestamp: now - 20, value: 1 },
+                { metricIndex: 0, timestamp: now - 10, value: 20 },
+            ],
+            [bounded, paged]
+        );
+
+        const boundedAccount = await program.account.metricAccount.fetch(bounded);
+        expect(boundedAccount.dataPoints.map((point) => point.value.toNumber())).to.deep.equal([
+            10, 20,
+        ]);
+        expect(boundedAccount.aggregates.count.toNumber()).to.equal(2);
+        expect(boundedAccount.aggregates.max.toNumber()).to.equal(20);
+        expect(boundedAccount.lastUpdated.toNumber()).to.equal(now - 10);
+
+        const pagedAccount = await program.account.metricAccount.fetch(paged);
+        expect(pagedAccount.aggregates.count.toNumber()).to.equal(0);
+        expect(pagedAccount.lastUpdated.toNumber()).to.equal(createdAt.toNumber());
+    });
+
+    it("evaluates alerts for every recorded entry", async () => {
+        const metric = await addMetric("batch_alerted");
+        const alert = alertPda(metric, 0);
+        await program.methods
+            .configureAlert({
+                thresholdType: { above: {} },
+                thresholdValue: new anchor.BN(50),
+                secondaryThreshold: null,
+                minTriggerInterval: 0,
+                requiredViolations: 1,
+                recoveryCount: 1,
+                hysteresis: new anchor.BN(0),
+                webhookUrl: null,
+            })
+            .accountsPartial({
+                monitoringState,
+                alertConfig: alert,
+                metricAccount: metric,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        await advanceClock(100);
+
+        await recordBatch(
+            [
+                { metricIndex: 0, timestamp: now - 20, value: 10 },
+                { metricIndex: 0, timestamp: now - 10, value: 60 },
+            ],
+            [metric, alert]
+        );
+
+        const alertAccount = await program.account.alertConfig.fetch(alert);
+        expect(alertAccount.state).to.deep.equal({ firing: {} });
+        expect(alertAccount.firingSince.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("rejects a batch missing one of the metric's alerts", async () => {
+        const metric = metricPda("batch_alerted");
+
+        try {
+            await recordBatch([{ metricIndex: 0, timestamp: now, value: 70 }], [metric]);
+            expect.fail("batch without the metric's alert should fail");
+        } catch (err) {
+            expect(String(err)).to.include("MissingMetricAlerts");
+        }
+
+        const metricAccount = await program.account.metricAccount.fetch(metric);
+        expect(metricAccount.lastUpdated.toNumber()).to.equal(now - 10);
+    });
+});
@@ -0,0 +1,245 @@
+import { startAnchor, Clock, ProgramTestContext } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import { ChainWatch } from "../target/types/chain_watch";
+
+describe("remove_metric", () => {
+    const CHAIN_WATCH_PROGRAM_ID = new PublicKey("8wq6TVhdTeWiUWXib3vxJguDE9Nm3dmV99YxFCWL3WEe");
+    const namespace = new anchor.BN(2);
+
+    let context: ProgramTestContext;
+    let program: Program<ChainWatch>;
+    let authority: Keypair;
+    let monitoringState: PublicKey;
+    let now: number;
+
+    const metricParams = (overrides: object = {}) => ({
+        minValue: null,
+        maxValue: null,
+        maxRateOfChange: null,
+        storeHistory: true,
+        retentionPeriod: 0,
+        ttlSeconds: 0,
+        kind: { gauge: {} },
+        ewmaAlphaBps: 0,
+        source: null,
+        scale: 0,
+        unit: "",
+        maxPages: 0,
+        rollups: [],
+        lateArrivalWindow: 0,
+        ...overrides,
+    });
+
+    const pda = (...seeds: Buffer[]) =>
+        PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    const metricPda = (name: string) =>
+        pda(Buffer.from("metric"), monitoringState.toBuffer(), Buffer.from(name));
+
+    const alertPda = (metric: PublicKey, alertId: number) =>
+        pda(
+            Buffer.from("alert"),
+            monitoringState.toBuffer(),
+            metric.toBuffer(),
+            new anchor.BN(alertId).toArrayLike(Buffer, "le", 2)
+        );
+
+    const pagePda = (metric: PublicKey, pageIndex: number) =>
+        pda(
+            Buffer.from("metric_page"),
+            metric.toBuffer(),
+            new anchor.BN(pageIndex).toArrayLike(Buffer, "le", 8)
+        );
+
+    const rollupPda = (metric: PublicKey, seconds: number) =>
+        pda(
+            Buffer.from("rollup"),
+            metric.toBuffer(),
+            new anchor.BN(seconds).toArrayLike(Buffer, "le", 8)
+        );
+
+    const compositePda = (compositeId: number) =>
+        pda(
+            Buffer.from("composite"),
+            monitoringState.toBuffer(),
+            new anchor.BN(compositeId).toArrayLike(Buffer, "le", 4)
+        );
+
+    const writable = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+
+    const addMetric = async (name: string, overrides: object = {}) => {
+        const metric = metricPda(name);
+        await program.methods
+            .addMetric(name, metricParams(overrides))
+            .accountsPartial({
+                monitoringState,
+                metricAccount: metric,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        return metric;
+    };
+
+    // Metrics start at the creation time, so points must be recorded after it
+    const advanceClock = async (seconds: number) => {
+        const clock = await context.banksClient.getClock();
+        now = Number(clock.unixTimestamp) + seconds;
+        context.setClock(
+            new Clock(
+                clock.slot,
+                clock.epochStartTimestamp,
+                clock.epoch,
+                clock.leaderScheduleEpoch,
+                BigInt(now)
+            )
+        );
+    };
+
+    const removeMetric = (metric: PublicKey, remaining: PublicKey[]) =>
+        program.methods
+            .removeMetric()
+            .accountsPartial({
+                monitoringState,
+                metricAccount: metric,
+                metricHistory: null,
+                authority: authority.publicKey,
+            })
+            .remainingAccounts(remaining.map(writable))
+            .rpc();
+
+    before(async () => {
+        context = await startAnchor(
+            "",
+            [{ name: "chain_watch", programId: CHAIN_WATCH_PROGRAM_ID }],
+            []
+        );
+        const provider = new BankrunProvider(context);
+        authority = context.payer;
+        program = new Program<ChainWatch>(require("../target/idl/chain_watch.json"), provider);
+
+        monitoringState = pda(
+            Buffer.from("monitoring"),
+            authority.publicKey.toBuffer(),
+            namespace.toArrayLike(Buffer, "le", 8)
+        );
+
+        await program.methods
+            .initializeMonitoring(namespace, {
+                maxMetrics: 10,
+                maxAlerts: 10,
+                maxDataPoints: 100,
+                minUpdateInterval: new anchor.BN(0),
+                rateLimitingEnabled: false,
+            })
+            .accountsPartial({
+                monitoringState,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+    });
+
+    it("closes pages, rollups, alerts and composite alerts with the metric", async () => {
+        const metric = await addMetric("removed", {
+            maxPages: 1,
+            rollups: [{ interval: { minute: {} }, retention: 4 }],
+        });
+        const other = await addMetric("survivor");
+        const alert = alertPda(metric, 0);
+        const page = pagePda(metric, 0);
+        const rollup = rollupPda(metric, 60);
+        const composite = compositePda(0);
+
+        await program.methods
+            .initializeRollup({ minute: {} })
+            .accountsPartial({
+                metricAccount: metric,
+                metricRollup: rollup,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        await program.methods
+            .configureAlert({
+                thresholdType: { above: {} },
+                thresholdValue: new anchor.BN(50),
+                secondaryThreshold: null,
+                minTriggerInterval: 0,
+                requiredViolations: 1,
+                recoveryCount: 1,
+                hysteresis: new anchor.BN(0),
+                webhookUrl: null,
+            })
+            .accountsPartial({
+                monitoringState,
+                alertConfig: alert,
+                metricAccount: metric,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        await advanceClock(10);
+
+        await program.methods
+            .recordMetric(new anchor.BN(now), new anchor.BN(10))
+            .accountsPartial({
+                monitoringState,
+                metricAccount: metric,
+                metricHistory: null,
+                metricPage: page,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .remainingAccounts([writable(rollup), writable(alert)])
+            .rpc();
+
+        const condition = (member: PublicKey) => ({
+            metric: {
+                metric: member,
+                thresholdType: { above: {} },
+                thresholdValue: new anchor.BN(0),
+                secondaryThreshold: null,
+            },
+        });
+        await program.methods
+            .createCompositeAlert({ and: {} }, [condition(metric), condition(other)])
+            .accountsPartial({
+                monitoringState,
+                compositeAlert: composite,
+                authority: authority.publicKey,
+                systemProgram: SystemProgram.programId,
+            })
+            .remainingAccounts([writable(metric), writable(other)])
+            .rpc();
+        expect(
+            (await program.account.metricAccount.fetch(other)).compositeCount
+        ).to.equal(1);
+
+        // Every dependent account must be supplied
+        try {
+            await removeMetric(metric, [alert, page, composite, other]);
+            expect.fail("removing without the rollup should fail");
+        } catch (err) {
+            expect(String(err)).to.include("RollupTierMismatch");
+        }
+
+        await removeMetric(metric, [alert, page, rollup, composite, other]);
+
+        for (const closed of [metric, alert, page, rollup, composite]) {
+            expect(await context.banksClient.getAccount(closed)).to.be.null;
+        }
+        const survivor = await program.account.metricAccount.fetch(other);
+        expect(survivor.compositeCount).to.equal(0);
+
+        const state = await program.account.monitoringState.fetch(monitoringState);
+        expect(state.metrics.map((key) => key.toBase58())).to.deep.equal([other.toBase58()]);
+        expect(state.alerts).to.have.length(0);
+        expect(state.compositeAlerts).to.have.length(0);
+    });
+});